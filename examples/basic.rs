@@ -2,11 +2,11 @@ use egui_backend::{
     egui,
     egui_glow::glow,
     fltk::{prelude::*, *},
+    FramePhase,
 };
 use fltk::enums::Mode;
 use fltk_egui as egui_backend;
-use std::rc::Rc;
-use std::{cell::RefCell, time::Instant};
+use std::{cell::RefCell, rc::Rc};
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 600;
 
@@ -35,12 +35,14 @@ fn main() {
             | enums::Event::KeyUp
             | enums::Event::MouseWheel
             | enums::Event::Resize
+            | enums::Event::Focus
+            | enums::Event::Leave
+            | enums::Event::Unfocus
             | enums::Event::Move
             | enums::Event::Drag => {
                 // Using "if let ..." for safety.
                 if let Ok(mut state) = state.try_borrow_mut() {
-                    state.fuse_input(win, ev);
-                    true
+                    state.fuse_input(win, ev)
                 } else {
                     false
                 }
@@ -50,10 +52,10 @@ fn main() {
     });
 
     let egui_ctx = egui::Context::default();
-    let start_time = Instant::now();
     let mut quit = false;
     let mut age: i32 = 17;
     let mut name: String = "".to_string();
+    let mut show_frame_stats = false;
 
     while fltk_app.wait() {
         // Clear the screen to dark red
@@ -61,7 +63,6 @@ fn main() {
         draw_background(gl);
 
         let mut state = state.borrow_mut();
-        state.input.time = Some(start_time.elapsed().as_secs_f64());
         let egui_output = egui_ctx.run(state.take_input(), |ctx| {
             egui::CentralPanel::default().show(&ctx, |ui| {
                 ui.heading("My egui Application");
@@ -74,6 +75,7 @@ fn main() {
                     age += 1;
                 }
                 ui.label(format!("Hello '{}', age {}", name, age));
+                ui.checkbox(&mut show_frame_stats, "Show frame stats");
                 ui.separator();
                 if ui
                     .button("Quit?")
@@ -83,11 +85,18 @@ fn main() {
                     quit = true;
                 }
             });
+            if show_frame_stats {
+                state.show_frame_stats(ctx);
+            }
         });
+        state.end_phase(FramePhase::Run);
+        state.update_interest(&mut win, &egui_ctx);
 
         if egui_output.repaint_after.is_zero() || state.window_resized() {
             state.fuse_output(&mut win, egui_output.platform_output);
             let meshes = egui_ctx.tessellate(egui_output.shapes);
+            state.end_phase(FramePhase::Tessellate);
+            state.count_frame_output(&meshes, &egui_output.textures_delta);
 
             painter.paint_and_update_textures(
                 state.canvas_size,
@@ -95,8 +104,10 @@ fn main() {
                 &meshes,
                 &egui_output.textures_delta,
             );
+            state.end_phase(FramePhase::Paint);
 
             win.swap_buffers();
+            state.end_phase(FramePhase::Swap);
             win.flush();
             app::awake();
         }
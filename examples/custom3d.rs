@@ -5,8 +5,7 @@ use egui_backend::{
 };
 use fltk::{app::App, window::GlWindow};
 use fltk_egui as egui_backend;
-use std::rc::Rc;
-use std::{cell::RefCell, time::Instant};
+use std::{cell::RefCell, rc::Rc};
 use three_d::*;
 
 const SCREEN_WIDTH: u32 = 800;
@@ -45,12 +44,14 @@ fn run_egui(fltk_app: App, mut win: GlWindow) {
             | enums::Event::KeyUp
             | enums::Event::MouseWheel
             | enums::Event::Resize
+            | enums::Event::Focus
+            | enums::Event::Leave
+            | enums::Event::Unfocus
             | enums::Event::Move
             | enums::Event::Drag => {
                 // Using "if let ..." for safety.
                 if let Ok(mut state) = state.try_borrow_mut() {
-                    state.fuse_input(win, ev);
-                    true
+                    state.fuse_input(win, ev)
                 } else {
                     false
                 }
@@ -60,7 +61,6 @@ fn run_egui(fltk_app: App, mut win: GlWindow) {
     });
 
     let egui_ctx = egui::Context::default();
-    let start_time = Instant::now();
 
     let mut angle = 45f32;
     while fltk_app.wait() {
@@ -69,7 +69,6 @@ fn run_egui(fltk_app: App, mut win: GlWindow) {
         draw_background(gl);
 
         let mut state = state.borrow_mut();
-        state.input.time = Some(start_time.elapsed().as_secs_f64());
         let egui_output = egui_ctx.run(state.take_input(), |ctx| {
             egui::CentralPanel::default().show(ctx, |ui| {
                 egui::widgets::global_dark_light_mode_buttons(ui);
@@ -88,25 +87,19 @@ fn run_egui(fltk_app: App, mut win: GlWindow) {
 
                         angle += response.drag_delta().x * 0.01;
 
-                        let callback = egui::PaintCallback {
-                            rect,
-                            callback: std::sync::Arc::new(egui_glow::CallbackFn::new(
-                                move |info, painter| {
-                                    with_three_d(painter.gl(), |three_d| {
-                                        three_d.frame(
-                                            FrameInput::new(&three_d.context, &info, painter),
-                                            angle,
-                                        );
-                                    });
-                                },
-                            )),
-                        };
+                        // Viewport, scissor and GL state are handled by the helper.
+                        let callback = egui_backend::glow_callback(rect, move |gl, viewport| {
+                            with_three_d(gl, |three_d| {
+                                three_d.frame(FrameInput::new(&three_d.context, viewport), angle);
+                            });
+                        });
                         ui.painter().add(callback);
                     });
                     ui.label("Drag to rotate!");
                 });
             });
         });
+        state.update_interest(&mut win, &egui_ctx);
 
         if egui_output.repaint_after.is_zero() || state.window_resized() {
             //Draw egui texture
@@ -162,61 +155,37 @@ pub struct FrameInput<'a> {
 }
 
 impl FrameInput<'_> {
-    pub fn new(
-        context: &three_d::Context,
-        info: &egui::PaintCallbackInfo,
-        painter: &egui_glow::Painter,
-    ) -> Self {
+    pub fn new(context: &three_d::Context, viewport: &egui_backend::GlViewport) -> Self {
         use three_d::*;
 
-        // Disable sRGB textures for three-d
-        #[cfg(not(target_arch = "wasm32"))]
-        #[allow(unsafe_code)]
-        unsafe {
-            use glow::HasContext as _;
-            context.disable(glow::FRAMEBUFFER_SRGB);
-        }
-
-        // Constructs a screen render target to render the final image to
-        let screen = painter.intermediate_fbo().map_or_else(
-            || {
-                RenderTarget::screen(
-                    context,
-                    info.viewport.width() as u32,
-                    info.viewport.height() as u32,
-                )
-            },
-            |fbo| {
-                RenderTarget::from_framebuffer(
-                    context,
-                    info.viewport.width() as u32,
-                    info.viewport.height() as u32,
-                    fbo,
-                )
-            },
-        );
+        // Render into the framebuffer egui paints into, which isn't always the screen
+        let [width, height] = viewport.screen_size_px;
+        let screen = match viewport.framebuffer {
+            Some(fbo) => RenderTarget::from_framebuffer(context, width, height, fbo),
+            None => RenderTarget::screen(context, width, height),
+        };
 
         // Set where to paint
-        let viewport = info.viewport_in_pixels();
-        let viewport = Viewport {
-            x: viewport.left_px.round() as _,
-            y: viewport.from_bottom_px.round() as _,
-            width: viewport.width_px.round() as _,
-            height: viewport.height_px.round() as _,
+        let [x, y, width, height] = viewport.viewport;
+        let viewport_box = Viewport {
+            x,
+            y,
+            width: width as _,
+            height: height as _,
         };
 
         // Respect the egui clip region (e.g. if we are inside an `egui::ScrollArea`).
-        let clip_rect = info.clip_rect_in_pixels();
+        let [x, y, width, height] = viewport.clip;
         let scissor_box = ScissorBox {
-            x: clip_rect.left_px.round() as _,
-            y: clip_rect.from_bottom_px.round() as _,
-            width: clip_rect.width_px.round() as _,
-            height: clip_rect.height_px.round() as _,
+            x,
+            y,
+            width: width as _,
+            height: height as _,
         };
         Self {
             screen,
             scissor_box,
-            viewport,
+            viewport: viewport_box,
         }
     }
 }
@@ -7,8 +7,7 @@ use egui_backend::{
 use egui_demo_lib::DemoWindows;
 use fltk::{app::App, window::GlutWindow};
 use fltk_egui as egui_backend;
-use std::rc::Rc;
-use std::{cell::RefCell, time::Instant};
+use std::{cell::RefCell, rc::Rc};
 
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 600;
@@ -34,8 +33,11 @@ fn main() {
 }
 
 fn run_egui(fltk_app: App, mut win: GlutWindow, demo: DemoWindows) {
-    // Init backend
-    let (mut painter, egui_state) = egui_backend::with_fltk(&mut win);
+    // Init backend, with vsync and at most 60 frames per second
+    let (mut painter, egui_state) = egui_backend::BackendBuilder::new()
+        .vsync(true)
+        .max_fps(60.0)
+        .build(&mut win);
     let state = Rc::new(RefCell::new(egui_state));
 
     win.handle({
@@ -47,12 +49,14 @@ fn run_egui(fltk_app: App, mut win: GlutWindow, demo: DemoWindows) {
             | enums::Event::KeyUp
             | enums::Event::MouseWheel
             | enums::Event::Resize
+            | enums::Event::Focus
+            | enums::Event::Leave
+            | enums::Event::Unfocus
             | enums::Event::Move
             | enums::Event::Drag => {
                 // Using "if let ..." for safety.
                 if let Ok(mut state) = state.try_borrow_mut() {
-                    state.fuse_input(win, ev);
-                    true
+                    state.fuse_input(win, ev)
                 } else {
                     false
                 }
@@ -62,19 +66,22 @@ fn run_egui(fltk_app: App, mut win: GlutWindow, demo: DemoWindows) {
     });
 
     let egui_ctx = egui::Context::default();
-    let start_time = Instant::now();
     let mut demo_windows = demo;
 
     while fltk_app.wait() {
+        let mut state = state.borrow_mut();
+        if !state.frame_due() {
+            continue;
+        }
+
         // Clear the screen to dark red
         let gl = painter.gl().as_ref();
         draw_background(gl);
 
-        let mut state = state.borrow_mut();
-        state.input.time = Some(start_time.elapsed().as_secs_f64());
         let egui_output = egui_ctx.run(state.take_input(), |ctx| {
             demo_windows.ui(&ctx);
         });
+        state.update_interest(&mut win, &egui_ctx);
 
         if egui_output.repaint_after.is_zero() || state.window_resized() {
             //Draw egui texture
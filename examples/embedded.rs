@@ -4,8 +4,7 @@ use egui_backend::{
     fltk::{enums::*, prelude::*, *},
 };
 use fltk_egui as egui_backend;
-use std::rc::Rc;
-use std::{cell::RefCell, time::Instant};
+use std::{cell::RefCell, rc::Rc};
 
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 600;
@@ -28,7 +27,6 @@ fn main() {
     frm.set_color(Color::Red.inactive());
     frm.set_frame(FrameType::FlatBox);
     let mut slider = valuator::Slider::default().with_type(valuator::SliderType::HorizontalFill);
-    slider.clear_visible_focus();
     slider.set_slider_frame(FrameType::RFlatBox);
     slider.set_slider_size(0.20);
     slider.set_color(Color::Blue.inactive());
@@ -50,10 +48,9 @@ fn main() {
         move |double_win, ev| match ev {
             enums::Event::Push
             | enums::Event::Released
-            | enums::Event::KeyDown
-            | enums::Event::KeyUp
             | enums::Event::MouseWheel
             | enums::Event::Resize
+            | enums::Event::Leave
             | enums::Event::Move
             | enums::Event::Drag => {
                 // Using "if let ..." for safety.
@@ -61,8 +58,27 @@ fn main() {
                     double_win.clear_damage();
                 }
                 if let Ok(mut state) = state.try_borrow_mut() {
-                    state.fuse_input(&mut w, ev);
-                    true
+                    state.fuse_input(&mut w, ev)
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        }
+    });
+
+    // Keyboard events go to the focused widget, so the GlWindow takes them itself,
+    // which also lets Tab move the focus between egui and the slider.
+    gl_win.handle({
+        let state = state.clone();
+        move |win, ev| match ev {
+            enums::Event::Focus
+            | enums::Event::Unfocus
+            | enums::Event::KeyDown
+            | enums::Event::KeyUp
+            | enums::Event::Shortcut => {
+                if let Ok(mut state) = state.try_borrow_mut() {
+                    state.fuse_input(win, ev)
                 } else {
                     false
                 }
@@ -75,7 +91,6 @@ fn main() {
     state.borrow_mut().set_visual_scale(1.5);
 
     let egui_ctx = egui::Context::default();
-    let start_time = Instant::now();
     let mut name = String::new();
     let mut age: i32 = 0;
     let mut quit = false;
@@ -86,7 +101,6 @@ fn main() {
         draw_background(gl);
 
         let mut state = state.borrow_mut();
-        state.input.time = Some(start_time.elapsed().as_secs_f64());
         frm.set_label(&format!("Hello {}", &name));
         slider.set_value(age as f64 / 120.);
         let egui_output = egui_ctx.run(state.take_input(), |ctx| {
@@ -111,6 +125,7 @@ fn main() {
                 }
             });
         });
+        state.update_interest(&mut gl_win, &egui_ctx);
 
         if egui_output.repaint_after.is_zero() || state.window_resized() {
             state.fuse_output(&mut gl_win, egui_output.platform_output);
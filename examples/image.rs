@@ -9,8 +9,7 @@ use fltk::{
     image::{JpegImage, SvgImage},
 };
 use fltk_egui as egui_backend;
-use std::rc::Rc;
-use std::{cell::RefCell, time::Instant};
+use std::{cell::RefCell, rc::Rc};
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 600;
 
@@ -23,11 +22,11 @@ fn main() {
     win.show();
     win.make_current();
 
-    // Init backend
-    let (mut painter, egui_state) = egui_backend::with_fltk(&mut win);
+    // Init backend, the painter is released with the window.
+    let (mut backend, egui_state) = egui_backend::BackendBuilder::new().build_backend(win.clone());
     let state = Rc::from(RefCell::from(egui_state));
 
-    win.handle({
+    backend.handle({
         let state = state.clone();
         move |win, ev| match ev {
             enums::Event::Push
@@ -36,12 +35,14 @@ fn main() {
             | enums::Event::KeyUp
             | enums::Event::MouseWheel
             | enums::Event::Resize
+            | enums::Event::Focus
+            | enums::Event::Leave
+            | enums::Event::Unfocus
             | enums::Event::Move
             | enums::Event::Drag => {
                 // Using "if let ..." for safety.
                 if let Ok(mut state) = state.try_borrow_mut() {
-                    state.fuse_input(win, ev);
-                    true
+                    state.fuse_input(win, ev)
                 } else {
                     false
                 }
@@ -60,16 +61,15 @@ fn main() {
         .unwrap();
 
     let egui_ctx = egui::Context::default();
-    let start_time = Instant::now();
     let mut quit = false;
 
     while fltk_app.wait() {
         // Clear the screen to dark red
-        let gl = painter.gl().as_ref();
-        draw_background(gl);
+        if let Some(gl) = backend.gl() {
+            draw_background(gl.as_ref());
+        }
 
         let mut state = state.borrow_mut();
-        state.input.time = Some(start_time.elapsed().as_secs_f64());
         let egui_output = egui_ctx.run(state.take_input(), |ctx| {
             egui::CentralPanel::default().show(&ctx, |ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
@@ -87,19 +87,20 @@ fn main() {
                 });
             });
         });
+        state.update_interest(&mut win, &egui_ctx);
 
         if egui_output.repaint_after.is_zero() || state.window_resized() {
             state.fuse_output(&mut win, egui_output.platform_output);
             let meshes = egui_ctx.tessellate(egui_output.shapes);
 
-            painter.paint_and_update_textures(
+            backend.paint_and_update_textures(
                 state.canvas_size,
                 state.pixels_per_point(),
                 &meshes,
                 &egui_output.textures_delta,
             );
 
-            win.swap_buffers();
+            backend.swap_buffers();
             win.flush();
             app::awake();
         }
@@ -108,8 +109,6 @@ fn main() {
             break;
         }
     }
-
-    painter.destroy();
 }
 
 fn draw_background<GL: glow::HasContext>(gl: &GL) {
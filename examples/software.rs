@@ -0,0 +1,100 @@
+use egui_backend::{
+    egui,
+    fltk::{prelude::*, *},
+};
+use fltk_egui as egui_backend;
+use std::{cell::RefCell, rc::Rc};
+const SCREEN_WIDTH: u32 = 800;
+const SCREEN_HEIGHT: u32 = 600;
+
+fn main() {
+    let fltk_app = app::App::default();
+    // A plain window, egui is painted on the CPU.
+    let mut win =
+        window::Window::new(100, 100, SCREEN_WIDTH as _, SCREEN_HEIGHT as _, None).center_screen();
+    win.end();
+    win.make_resizable(true);
+    win.show();
+
+    // Init backend
+    let (mut painter, egui_state) = egui_backend::with_software(&mut win);
+    painter.clear_color = egui::Color32::from_rgb(153, 77, 77);
+    let painter = Rc::from(RefCell::from(painter));
+    let state = Rc::from(RefCell::from(egui_state));
+
+    win.draw({
+        let painter = painter.clone();
+        move |_| painter.borrow_mut().draw(0, 0)
+    });
+
+    win.handle({
+        let state = state.clone();
+        move |win, ev| match ev {
+            enums::Event::Push
+            | enums::Event::Released
+            | enums::Event::KeyDown
+            | enums::Event::KeyUp
+            | enums::Event::MouseWheel
+            | enums::Event::Resize
+            | enums::Event::Focus
+            | enums::Event::Leave
+            | enums::Event::Unfocus
+            | enums::Event::Move
+            | enums::Event::Drag => {
+                // Using "if let ..." for safety.
+                if let Ok(mut state) = state.try_borrow_mut() {
+                    state.fuse_input(win, ev)
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        }
+    });
+
+    let egui_ctx = egui::Context::default();
+    let mut quit = false;
+    let mut age: i32 = 17;
+    let mut name: String = "".to_string();
+
+    while fltk_app.wait() {
+        let mut state = state.borrow_mut();
+        let egui_output = egui_ctx.run(state.take_input(), |ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(&ctx, |ui| {
+                    ui.heading("My egui Application, without OpenGL");
+                    ui.horizontal(|ui| {
+                        ui.label("Your name: ");
+                        ui.text_edit_singleline(&mut name);
+                    });
+                    ui.add(egui::Slider::new(&mut age, 0..=120).text("age"));
+                    if ui.button("Click each year").clicked() {
+                        age += 1;
+                    }
+                    ui.label(format!("Hello '{}', age {}", name, age));
+                    ui.separator();
+                    if ui.button("Quit?").clicked() {
+                        quit = true;
+                    }
+                });
+        });
+        state.update_interest(&mut win, &egui_ctx);
+
+        if egui_output.repaint_after.is_zero() || state.window_resized() {
+            state.fuse_output(&mut win, egui_output.platform_output);
+            let meshes = egui_ctx.tessellate(egui_output.shapes);
+            painter.borrow_mut().paint_and_update_textures(
+                state.canvas_size,
+                state.pixels_per_point(),
+                &meshes,
+                &egui_output.textures_delta,
+            );
+            win.redraw();
+        }
+
+        if quit {
+            break;
+        }
+    }
+}
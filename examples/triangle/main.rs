@@ -8,7 +8,6 @@ use egui_backend::{
 use fltk_egui as egui_backend;
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
 mod triangle;
 
 const SCREEN_WIDTH: u32 = 800;
@@ -20,7 +19,8 @@ fn main() {
     let fltk_app = app::App::default();
     let mut win = window::GlWindow::new(100, 100, SCREEN_WIDTH as _, SCREEN_HEIGHT as _, None)
         .center_screen();
-    win.set_mode(Mode::MultiSample);
+    // The triangle's shaders are GLSL 1.50.
+    win.set_mode(Mode::Opengl3 | Mode::MultiSample);
     win.end();
     win.make_resizable(true);
     win.show();
@@ -39,13 +39,14 @@ fn main() {
             | enums::Event::KeyUp
             | enums::Event::MouseWheel
             | enums::Event::Resize
+            | enums::Event::Leave
+            | enums::Event::Unfocus
             | enums::Event::Move
             | enums::Event::Focus
             | enums::Event::Drag => {
                 // Using "if let ..." for safety.
                 if let Ok(mut state) = state.try_borrow_mut() {
-                    state.fuse_input(win, ev);
-                    true
+                    state.fuse_input(win, ev)
                 } else {
                     false
                 }
@@ -63,7 +64,6 @@ fn main() {
     let mut texture: Option<TextureHandle> = None;
 
     let egui_ctx = egui::Context::default();
-    let start_time = Instant::now();
     let mut test_str: String =
         "A text box to write in. Cut, copy, paste commands are available.".to_owned();
     let mut quit = false;
@@ -74,7 +74,6 @@ fn main() {
         draw_background(gl);
 
         let mut state = state.borrow_mut();
-        state.input.time = Some(start_time.elapsed().as_secs_f64());
         let egui_output = egui_ctx.run(state.take_input(), |ctx| {
             // Draw our triangle.
             triangle.draw(gl);
@@ -128,6 +127,7 @@ fn main() {
                 }
             });
         });
+        state.update_interest(&mut win, &egui_ctx);
 
         if egui_output.repaint_after.is_zero() || state.window_resized() {
             state.fuse_output(&mut win, egui_output.platform_output);
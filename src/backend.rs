@@ -0,0 +1,186 @@
+use crate::{BackendBuilder, EguiState};
+use egui::{ClippedPrimitive, ColorImage, TexturesDelta};
+use egui_glow::{glow, Painter};
+use fltk::{
+    enums::Event,
+    prelude::{WidgetExt, WindowExt},
+    window::GlWindow,
+};
+use std::{
+    cell::{RefCell, RefMut},
+    rc::Rc,
+    sync::Arc,
+};
+
+type Handler = Box<dyn FnMut(&mut GlWindow, Event) -> bool>;
+
+/// The GlWindow and its egui_glow painter, whose GL resources are released in time.
+///
+/// FLTK deletes the GL context when the window is hidden, so the painter is destroyed
+/// (with the context made current) when the window is closed, by `GlBackend::hide`,
+/// or at last on drop. Afterwards painting does nothing and `painter()` returns None.
+///
+/// Hide the window with `GlBackend::hide` rather than `GlWindow::hide`: FLTK reports
+/// `Event::Hide` once the context is gone. The backend still drops the painter then,
+/// without its GL resources which went away with the context, but not when the window
+/// is only iconified, which keeps the context.
+///
+/// The backend sets the window's handle() and callback(): use `GlBackend::handle`
+/// instead of `GlWindow::handle`, closing the window hides it as usual.
+///
+/// ```no_run
+/// # use fltk_egui::{fltk::window::GlWindow, BackendBuilder};
+/// # fn init(win: GlWindow) {
+/// let (mut backend, state) = BackendBuilder::new().build_backend(win);
+/// backend.handle(move |win, ev| false /* state.fuse_input(win, ev) */);
+/// // No painter.destroy() after the event loop.
+/// # }
+/// ```
+pub struct GlBackend {
+    window: GlWindow,
+    painter: Rc<RefCell<Option<Painter>>>,
+    handler: Rc<RefCell<Option<Handler>>>,
+}
+
+impl BackendBuilder {
+    /// Same as build(), the painter and the window are owned by a [`GlBackend`].
+    pub fn build_backend(self, mut win: GlWindow) -> (GlBackend, EguiState) {
+        let (painter, state) = self.build(&mut win);
+        (GlBackend::new(win, painter), state)
+    }
+}
+
+impl GlBackend {
+    fn new(mut window: GlWindow, painter: Painter) -> Self {
+        let painter = Rc::new(RefCell::new(Some(painter)));
+        let handler: Rc<RefCell<Option<Handler>>> = Rc::default();
+
+        window.handle({
+            let handler = handler.clone();
+            let painter = painter.clone();
+            move |win, ev| {
+                // Iconified windows stay shown.
+                if ev == Event::Hide && !win.shown() {
+                    release(win, &painter);
+                }
+                match handler.try_borrow_mut() {
+                    Ok(mut handler) => handler.as_mut().map_or(false, |handler| handler(win, ev)),
+                    Err(_) => false,
+                }
+            }
+        });
+        // Hiding deletes the context, release before.
+        window.set_callback({
+            let painter = painter.clone();
+            move |win| {
+                release(win, &painter);
+                win.hide();
+            }
+        });
+
+        Self {
+            window,
+            painter,
+            handler,
+        }
+    }
+
+    /// Handle the window's events, in place of `GlWindow::handle`.
+    pub fn handle(&mut self, handler: impl FnMut(&mut GlWindow, Event) -> bool + 'static) {
+        *self.handler.borrow_mut() = Some(Box::new(handler));
+    }
+
+    pub fn window(&self) -> &GlWindow {
+        &self.window
+    }
+
+    pub fn window_mut(&mut self) -> &mut GlWindow {
+        &mut self.window
+    }
+
+    /// The painter, None once released.
+    pub fn painter(&self) -> Option<RefMut<'_, Painter>> {
+        let painter = self.painter.borrow_mut();
+        if painter.is_some() {
+            Some(RefMut::map(painter, |painter| painter.as_mut().unwrap()))
+        } else {
+            None
+        }
+    }
+
+    /// The OpenGL context, None once released.
+    pub fn gl(&self) -> Option<Arc<glow::Context>> {
+        self.painter
+            .borrow()
+            .as_ref()
+            .map(|painter| painter.gl().clone())
+    }
+
+    pub fn is_released(&self) -> bool {
+        self.painter.borrow().is_none()
+    }
+
+    /// Paint the frame, does nothing once released.
+    pub fn paint_and_update_textures(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clipped_primitives: &[ClippedPrimitive],
+        textures_delta: &TexturesDelta,
+    ) {
+        if let Some(painter) = self.painter.borrow_mut().as_mut() {
+            painter.paint_and_update_textures(
+                screen_size_px,
+                pixels_per_point,
+                clipped_primitives,
+                textures_delta,
+            );
+        }
+    }
+
+    /// Reads back the painted frame, see [`capture`](crate::capture). None once released.
+    pub fn capture(&self) -> Option<ColorImage> {
+        self.painter
+            .borrow()
+            .as_ref()
+            .map(|painter| crate::capture(painter, &self.window))
+    }
+
+    /// Show the painted frame, does nothing once released.
+    pub fn swap_buffers(&mut self) {
+        if !self.is_released() {
+            self.window.swap_buffers();
+        }
+    }
+
+    /// Destroy the painter's GL resources now.
+    pub fn release(&mut self) {
+        release(&mut self.window, &self.painter);
+    }
+
+    /// Release the painter, then hide the window (which deletes its context).
+    pub fn hide(&mut self) {
+        self.release();
+        self.window.hide();
+    }
+}
+
+impl Drop for GlBackend {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn release(window: &mut GlWindow, painter: &RefCell<Option<Painter>>) {
+    let painter = painter
+        .try_borrow_mut()
+        .ok()
+        .and_then(|mut painter| painter.take());
+    if let Some(mut painter) = painter {
+        // Otherwise the resources went away with the context (or the window).
+        if !window.was_deleted() && window.context().is_some() {
+            window.make_current();
+            painter.destroy();
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use egui::{Color32, ColorImage};
+use egui_glow::{
+    glow::{self, HasContext},
+    Painter,
+};
+use fltk::{enums::Mode, image::RgbImage, prelude::FltkError, window::GlWindow};
+
+use crate::ColorImageExt;
+
+/// Reads back what `painter` painted on the GlWindow, e.g. to attach a screenshot to a bug report.
+///
+/// Call it after `paint_and_update_textures` and before `swap_buffers`, the image has
+/// the size of the framebuffer in pixels (larger than the window on HiDPI screens).
+///
+/// The back buffer is read for `Mode::Double` windows, the front buffer otherwise.
+pub fn capture(painter: &Painter, win: &GlWindow) -> ColorImage {
+    let gl = painter.gl();
+    let buffer = if win.mode().contains(Mode::Double) {
+        glow::BACK
+    } else {
+        glow::FRONT
+    };
+    unsafe {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        let previous = gl.get_parameter_i32(glow::READ_BUFFER) as u32;
+        gl.read_buffer(buffer);
+        let image = read_pixels(gl, [win.pixel_w() as u32, win.pixel_h() as u32]);
+        gl.read_buffer(previous);
+        image
+    }
+}
+
+/// Same as [`capture`], as an FLTK image.
+pub fn capture_rgb_image(painter: &Painter, win: &GlWindow) -> Result<RgbImage, FltkError> {
+    capture(painter, win).to_rgb_image()
+}
+
+/// Reads the bound framebuffer, top row first.
+pub(crate) unsafe fn read_pixels(gl: &glow::Context, [width, height]: [u32; 2]) -> ColorImage {
+    let mut rgba = vec![0; width as usize * height as usize * 4];
+    gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+    gl.read_pixels(
+        0,
+        0,
+        width as i32,
+        height as i32,
+        glow::RGBA,
+        glow::UNSIGNED_BYTE,
+        glow::PixelPackData::Slice(&mut rgba),
+    );
+
+    // GL rows go bottom to top.
+    let row = (width as usize * 4).max(1);
+    let pixels = rgba
+        .chunks_exact(row)
+        .rev()
+        .flat_map(|row| row.chunks_exact(4))
+        .map(|p| Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3]))
+        .collect();
+    ColorImage {
+        size: [width as usize, height as usize],
+        pixels,
+    }
+}
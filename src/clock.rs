@@ -0,0 +1,61 @@
+use std::{cmp::Ordering, collections::VecDeque, time::Instant};
+
+/// Frame intervals the predicted_dt estimate is based on.
+const SAMPLES: usize = 16;
+/// Longer intervals are the event loop idling between events, not a refresh rate.
+const MAX_INTERVAL: f64 = 0.1;
+
+/// Owns the time of the frames given to egui and estimates the next frame's duration.
+pub(crate) struct FrameClock {
+    start: Instant,
+    last_frame: Option<f64>,
+    intervals: VecDeque<f64>,
+    /// Overrides the estimate, see `EguiState::set_refresh_rate`.
+    pub refresh_rate: Option<f32>,
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            last_frame: None,
+            intervals: VecDeque::with_capacity(SAMPLES),
+            refresh_rate: None,
+        }
+    }
+}
+
+impl FrameClock {
+    /// Seconds since the clock started.
+    pub fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    /// A frame starts at `time`, remember the interval since the previous one.
+    pub fn frame(&mut self, time: f64) {
+        if let Some(last) = self.last_frame {
+            let interval = time - last;
+            if interval > 0.0 && interval < MAX_INTERVAL {
+                if self.intervals.len() == SAMPLES {
+                    self.intervals.pop_front();
+                }
+                self.intervals.push_back(interval);
+            }
+        }
+        self.last_frame = Some(time);
+    }
+
+    /// The expected duration of the next frame: the refresh rate when set,
+    /// otherwise the median of the recent continuous frame intervals (1/60 until known).
+    pub fn predicted_dt(&self) -> f32 {
+        if let Some(rate) = self.refresh_rate.filter(|rate| *rate > 0.0) {
+            return 1.0 / rate;
+        }
+        if self.intervals.is_empty() {
+            return 1.0 / 60.0;
+        }
+        let mut intervals: Vec<f64> = self.intervals.iter().copied().collect();
+        intervals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        intervals[intervals.len() / 2] as f32
+    }
+}
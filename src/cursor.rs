@@ -0,0 +1,284 @@
+use egui::CursorIcon;
+use fltk::{enums, image::RgbImage, prelude::WidgetExt};
+
+/// Side length (in pixels) of the built-in cursor images.
+const CURSOR_SIZE: usize = 24;
+
+/// An RGBA cursor image with its hotspot, used when FLTK has no matching system cursor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CursorImage {
+    /// Width and height of the image in pixels.
+    pub size: [usize; 2],
+    /// Unmultiplied RGBA pixels, row by row.
+    pub rgba: Vec<u8>,
+    /// The pixel of the image that acts as the pointer position.
+    pub hotspot: [i32; 2],
+}
+
+impl CursorImage {
+    /// Construct a cursor image from unmultiplied RGBA pixels.
+    pub fn new(size: [usize; 2], rgba: Vec<u8>, hotspot: [i32; 2]) -> Self {
+        assert_eq!(
+            size[0] * size[1] * 4,
+            rgba.len(),
+            "cursor image size does not match its pixel data"
+        );
+        Self {
+            size,
+            rgba,
+            hotspot,
+        }
+    }
+
+    /// Construct a cursor image from an egui::ColorImage.
+    pub fn from_color_image(image: &egui::ColorImage, hotspot: [i32; 2]) -> Self {
+        let rgba = image
+            .pixels
+            .iter()
+            .flat_map(|c| c.to_srgba_unmultiplied())
+            .collect();
+        Self::new(image.size, rgba, hotspot)
+    }
+
+    fn to_rgb_image(&self) -> Option<RgbImage> {
+        RgbImage::new(
+            &self.rgba,
+            self.size[0] as i32,
+            self.size[1] as i32,
+            enums::ColorDepth::Rgba8,
+        )
+        .ok()
+    }
+}
+
+/// The default cursor
+pub struct FusedCursor {
+    /// The FLTK cursor currently set on the window (Arrow while an image cursor is shown).
+    pub cursor_icon: enums::Cursor,
+    egui_icon: Option<CursorIcon>,
+    images: Vec<(CursorIcon, CursorImage)>,
+}
+
+const ARROW: enums::Cursor = enums::Cursor::Arrow;
+
+impl FusedCursor {
+    /// Construct a new cursor
+    pub fn new() -> Self {
+        Self {
+            cursor_icon: ARROW,
+            egui_icon: None,
+            images: Vec::new(),
+        }
+    }
+
+    /// Use a custom image whenever egui asks for `icon`, overriding both the FLTK and built-in cursors.
+    pub fn register_image(&mut self, icon: CursorIcon, image: CursorImage) {
+        self.unregister_image(icon);
+        self.images.push((icon, image));
+        if self.egui_icon == Some(icon) {
+            // Force the new image to be applied on the next frame.
+            self.egui_icon = None;
+        }
+    }
+
+    /// Remove a custom image previously registered for `icon`.
+    pub fn unregister_image(&mut self, icon: CursorIcon) -> Option<CursorImage> {
+        let idx = self.images.iter().position(|(i, _)| *i == icon)?;
+        if self.egui_icon == Some(icon) {
+            self.egui_icon = None;
+        }
+        Some(self.images.remove(idx).1)
+    }
+
+    /// The custom image registered for `icon`, if any.
+    pub fn registered_image(&self, icon: CursorIcon) -> Option<&CursorImage> {
+        self.images
+            .iter()
+            .find(|(i, _)| *i == icon)
+            .map(|(_, image)| image)
+    }
+}
+
+impl Default for FusedCursor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Translates FLTK cursor to Egui cursors
+///
+/// The cursor is set on `win`, or on the window containing it when it isn't a window.
+pub fn translate_cursor<W: WidgetExt>(
+    win: &mut W,
+    fused: &mut FusedCursor,
+    cursor_icon: egui::CursorIcon,
+) {
+    if fused.egui_icon == Some(cursor_icon) {
+        return;
+    }
+    let mut win = match win.as_window().or_else(|| win.window()) {
+        Some(window) => window,
+        None => return,
+    };
+    fused.egui_icon = Some(cursor_icon);
+
+    let image = match fused.registered_image(cursor_icon) {
+        Some(image) => Some(image.clone()),
+        None => match fltk_cursor(cursor_icon) {
+            Some(cursor) => {
+                fused.cursor_icon = cursor;
+                win.set_cursor(cursor);
+                return;
+            }
+            None => builtin_cursor_image(cursor_icon),
+        },
+    };
+
+    fused.cursor_icon = ARROW;
+    match image.and_then(|image| Some((image.to_rgb_image()?, image.hotspot))) {
+        Some((rgb, [hot_x, hot_y])) => win.set_cursor_image(rgb, hot_x, hot_y),
+        None => win.set_cursor(ARROW),
+    }
+}
+
+/// The closest FLTK system cursor, or None when an image has to be drawn instead.
+pub fn fltk_cursor(cursor_icon: egui::CursorIcon) -> Option<enums::Cursor> {
+    let cursor = match cursor_icon {
+        CursorIcon::None => enums::Cursor::None,
+        CursorIcon::Default => enums::Cursor::Arrow,
+        CursorIcon::Help => enums::Cursor::Help,
+        CursorIcon::PointingHand => enums::Cursor::Hand,
+        CursorIcon::Progress | CursorIcon::Wait => enums::Cursor::Wait,
+        CursorIcon::Crosshair => enums::Cursor::Cross,
+        CursorIcon::Text => enums::Cursor::Insert,
+        CursorIcon::Move | CursorIcon::AllScroll => enums::Cursor::Move,
+        CursorIcon::ResizeHorizontal | CursorIcon::ResizeColumn => enums::Cursor::WE,
+        CursorIcon::ResizeVertical | CursorIcon::ResizeRow => enums::Cursor::NS,
+        CursorIcon::ResizeNeSw => enums::Cursor::NESW,
+        CursorIcon::ResizeNwSe => enums::Cursor::NWSE,
+        CursorIcon::ResizeEast => enums::Cursor::E,
+        CursorIcon::ResizeSouthEast => enums::Cursor::SE,
+        CursorIcon::ResizeSouth => enums::Cursor::S,
+        CursorIcon::ResizeSouthWest => enums::Cursor::SW,
+        CursorIcon::ResizeWest => enums::Cursor::W,
+        CursorIcon::ResizeNorthWest => enums::Cursor::NW,
+        CursorIcon::ResizeNorth => enums::Cursor::N,
+        CursorIcon::ResizeNorthEast => enums::Cursor::NE,
+        CursorIcon::ContextMenu
+        | CursorIcon::Cell
+        | CursorIcon::VerticalText
+        | CursorIcon::Alias
+        | CursorIcon::Copy
+        | CursorIcon::NoDrop
+        | CursorIcon::NotAllowed
+        | CursorIcon::ZoomIn
+        | CursorIcon::ZoomOut
+        | CursorIcon::Grab
+        | CursorIcon::Grabbing => return None,
+    };
+    Some(cursor)
+}
+
+/// Built-in images for the cursors FLTK does not provide.
+pub fn builtin_cursor_image(cursor_icon: egui::CursorIcon) -> Option<CursorImage> {
+    let c = CURSOR_SIZE as f32 / 2.0;
+    let image = match cursor_icon {
+        CursorIcon::NotAllowed | CursorIcon::NoDrop => draw_cursor([c as i32, c as i32], |x, y| {
+            let r = ((x - c).powi(2) + (y - c).powi(2)).sqrt();
+            let ring = (r - 8.0).abs() < 1.5;
+            let slash = ((x - c) + (y - c)).abs() < 1.5 && r < 8.0;
+            ring || slash
+        }),
+        CursorIcon::ZoomIn | CursorIcon::ZoomOut => {
+            let zoom_in = cursor_icon == CursorIcon::ZoomIn;
+            draw_cursor([9, 9], move |x, y| {
+                let r = ((x - 9.0).powi(2) + (y - 9.0).powi(2)).sqrt();
+                let lens = (r - 6.5).abs() < 1.2;
+                let handle = (x - y).abs() < 1.8 && x > 13.5 && x < 21.0;
+                let minus = (y - 9.0).abs() < 0.8 && (x - 9.0).abs() < 3.5;
+                let plus = zoom_in && (x - 9.0).abs() < 0.8 && (y - 9.0).abs() < 3.5;
+                lens || handle || minus || plus
+            })
+        }
+        CursorIcon::Grab => draw_cursor([c as i32, c as i32], |x, y| {
+            // Spread fingers above the palm, the thumb on the left.
+            let tips = [6.0, 4.0, 4.5, 7.0];
+            let finger = tips
+                .iter()
+                .enumerate()
+                .any(|(i, &tip)| (x - (7.5 + 3.0 * i as f32)).abs() < 1.1 && y > tip && y < 12.0);
+            let thumb = (x - (3.0 + (y - 9.0) * 0.65)).abs() < 1.1 && y > 9.0 && y < 16.0;
+            finger || thumb || palm(x, y)
+        }),
+        CursorIcon::Grabbing => draw_cursor([c as i32, c as i32], |x, y| {
+            // Fingers folded over the palm, only the knuckles show.
+            let knuckle = (0..4).any(|i| (x - (7.5 + 3.0 * i as f32)).abs() < 1.1 && y > 8.5);
+            let thumb = (y - 13.0).abs() < 1.1 && x > 4.0 && x < 8.0;
+            (knuckle && y < 12.0) || thumb || palm(x, y)
+        }),
+        CursorIcon::Cell => draw_cursor([c as i32, c as i32], |x, y| {
+            let dx = (x - c).abs();
+            let dy = (y - c).abs();
+            (dx < 2.0 && dy < 9.0) || (dy < 2.0 && dx < 9.0)
+        }),
+        CursorIcon::VerticalText => draw_cursor([c as i32, c as i32], |x, y| {
+            let dx = (x - c).abs();
+            let dy = (y - c).abs();
+            (dy < 0.8 && dx < 7.0) || (dx > 5.5 && dx < 7.0 && dy < 3.0)
+        }),
+        CursorIcon::Copy => draw_cursor([1, 1], |x, y| {
+            let plus = ((x - 17.0).abs() < 1.0 && (y - 17.0).abs() < 4.5)
+                || ((y - 17.0).abs() < 1.0 && (x - 17.0).abs() < 4.5);
+            arrow(x, y) || plus
+        }),
+        CursorIcon::Alias => draw_cursor([1, 1], |x, y| {
+            let shaft = (x - (40.0 - y)).abs() < 1.2 && x > 14.0 && x < 22.0;
+            let head = ((y - 14.5).abs() < 1.0 && x > 17.0 && x < 23.0)
+                || ((x - 22.0).abs() < 1.0 && y > 14.0 && y < 20.0);
+            arrow(x, y) || shaft || head
+        }),
+        CursorIcon::ContextMenu => draw_cursor([1, 1], |x, y| {
+            let in_box = x > 13.0 && x < 23.0 && y > 12.0 && y < 23.0;
+            let border = in_box && !(x > 14.0 && x < 22.0 && y > 13.0 && y < 22.0);
+            let lines = in_box && x > 15.0 && x < 21.0 && ((y - 15.5) % 3.0).abs() < 0.8;
+            arrow(x, y) || border || lines
+        }),
+        _ => return None,
+    };
+    Some(image)
+}
+
+/// The palm of the hand cursors.
+fn palm(x: f32, y: f32) -> bool {
+    x > 6.0 && x < 18.0 && y > 11.0 && y < 20.0
+}
+
+/// A plain pointer arrow with its tip at the top-left corner.
+fn arrow(x: f32, y: f32) -> bool {
+    x >= 1.0 && y >= 1.0 && y < 16.0 && x <= y * 0.6 + 1.0
+}
+
+/// Rasterizes a mask into a black cursor with a white outline.
+fn draw_cursor(hotspot: [i32; 2], mask: impl Fn(f32, f32) -> bool) -> CursorImage {
+    let inside = |x: isize, y: isize| -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < CURSOR_SIZE
+            && (y as usize) < CURSOR_SIZE
+            && mask(x as f32 + 0.5, y as f32 + 0.5)
+    };
+    let mut rgba = Vec::with_capacity(CURSOR_SIZE * CURSOR_SIZE * 4);
+    for y in 0..CURSOR_SIZE as isize {
+        for x in 0..CURSOR_SIZE as isize {
+            let pixel = if inside(x, y) {
+                [0, 0, 0, 255]
+            } else if (-1..=1).any(|dy| (-1..=1).any(|dx| inside(x + dx, y + dy))) {
+                [255, 255, 255, 255]
+            } else {
+                [0, 0, 0, 0]
+            };
+            rgba.extend_from_slice(&pixel);
+        }
+    }
+    CursorImage::new([CURSOR_SIZE, CURSOR_SIZE], rgba, hotspot)
+}
@@ -0,0 +1,464 @@
+use crate::{focus::focus_fltk_neighbour, EguiState};
+use fltk::{
+    app, enums,
+    prelude::{WidgetBase, WidgetExt},
+    widget::Widget,
+};
+
+/// Where `translate_event` reads the details of an FLTK event from.
+///
+/// FltkEvents reads FLTK's global event state and is what `EguiState::fuse_input` uses,
+/// ScriptedEvents lets tests feed events without a display.
+pub trait EventSource {
+    /// Coordinates of the event, relative to the GlWindow.
+    fn event_coords(&self) -> (i32, i32);
+    /// Size of the GlWindow.
+    fn window_size(&self) -> (i32, i32);
+    /// The mouse button pushed or released, see `translate_mouse_button`.
+    fn event_button(&self) -> i32;
+    fn event_key(&self) -> enums::Key;
+    /// Text typed by the event.
+    fn event_text(&self) -> String;
+    /// Modifier keys held during the event.
+    fn event_state(&self) -> enums::EventState;
+    fn event_dy(&self) -> app::MouseWheel;
+    /// Same as `app::compose()`: Some if the event text is to be inserted.
+    fn compose(&mut self) -> Option<i32>;
+    fn compose_reset(&mut self);
+    /// Give the keyboard focus to the next (or previous) FLTK widget, false if there is none.
+    fn focus_neighbour(&mut self, backward: bool) -> bool;
+    /// Give the keyboard focus to the GlWindow, once the current event has been handled.
+    fn take_focus_later(&mut self);
+
+    fn pointer_in_window(&self) -> bool {
+        let (x, y) = self.event_coords();
+        let (w, h) = self.window_size();
+        x >= 0 && y >= 0 && x < w && y < h
+    }
+
+    fn has_modifier(&self, modifier: enums::EventState) -> bool {
+        self.event_state() & modifier == modifier
+    }
+}
+
+/// Reads events from FLTK, for a GlWindow (possibly forwarded from its parent window),
+/// or any other widget egui is drawn into.
+pub struct FltkEvents<'a, W: WidgetExt> {
+    win: &'a mut W,
+}
+
+impl<'a, W: WidgetExt> FltkEvents<'a, W> {
+    pub fn new(win: &'a mut W) -> Self {
+        Self { win }
+    }
+}
+
+impl<W: WidgetExt> EventSource for FltkEvents<'_, W> {
+    /// `app::event_coords()` is relative to the window that received the event, which is
+    /// a parent window when its handler forwards events to the GlWindow.
+    fn event_coords(&self) -> (i32, i32) {
+        let (x, y) = root_position(self.win);
+        (app::event_x_root() - x, app::event_y_root() - y)
+    }
+
+    fn window_size(&self) -> (i32, i32) {
+        (self.win.width(), self.win.height())
+    }
+
+    fn event_button(&self) -> i32 {
+        app::event_button()
+    }
+
+    fn event_key(&self) -> enums::Key {
+        app::event_key()
+    }
+
+    fn event_text(&self) -> String {
+        app::event_text()
+    }
+
+    fn event_state(&self) -> enums::EventState {
+        app::event_state()
+    }
+
+    fn event_dy(&self) -> app::MouseWheel {
+        app::event_dy()
+    }
+
+    fn compose(&mut self) -> Option<i32> {
+        app::compose()
+    }
+
+    fn compose_reset(&mut self) {
+        app::compose_reset()
+    }
+
+    fn focus_neighbour(&mut self, backward: bool) -> bool {
+        focus_fltk_neighbour(self.win, backward)
+    }
+
+    fn take_focus_later(&mut self) {
+        // The resulting Focus event is delivered while the state is still borrowed.
+        if let Some(mut win) = Widget::from_dyn_widget(&*self.win) {
+            app::awake_callback(move || {
+                if !win.was_deleted() {
+                    win.take_focus().ok();
+                }
+            });
+        }
+    }
+}
+
+/// Position of a widget on the screen, windows (e.g. a GlWindow) being placed relative
+/// to their parent window and other widgets relative to their window.
+fn root_position<W: WidgetExt>(widget: &W) -> (i32, i32) {
+    match (widget.as_window(), widget.window()) {
+        (Some(win), _) => (win.x_root(), win.y_root()),
+        (None, Some(win)) => (win.x_root() + widget.x(), win.y_root() + widget.y()),
+        (None, None) => (widget.x(), widget.y()),
+    }
+}
+
+/// Feeds scripted events to an EguiState, to test the event translation without FLTK.
+///
+/// ```
+/// use fltk_egui::{egui, fltk::enums::EventState, EguiState, ScriptedEvents};
+///
+/// let mut state = EguiState::with_size([800, 600], 1.0, 2048);
+/// let mut events = ScriptedEvents::new(800, 600);
+/// events.click(&mut state, 10, 20);
+/// events.type_text(&mut state, "abc");
+/// events.key(&mut state, 'c', EventState::Command);
+///
+/// let input = state.take_input();
+/// assert!(input.events.contains(&egui::Event::Text("a".into())));
+/// assert!(input.events.contains(&egui::Event::Copy));
+/// ```
+pub struct ScriptedEvents {
+    size: (i32, i32),
+    coords: (i32, i32),
+    button: i32,
+    buttons_down: Vec<i32>,
+    key: enums::Key,
+    text: String,
+    state: enums::EventState,
+    dy: app::MouseWheel,
+    /// How many times the focus was handed to a neighbouring FLTK widget.
+    pub focus_handoffs: usize,
+}
+
+impl ScriptedEvents {
+    /// Construct a script for a GlWindow of the given size.
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            size: (width, height),
+            coords: (0, 0),
+            button: 0,
+            buttons_down: Vec::new(),
+            key: enums::Key::from_i32(0),
+            text: String::new(),
+            state: enums::EventState::None,
+            dy: app::MouseWheel::None,
+            focus_handoffs: 0,
+        }
+    }
+
+    /// Send a raw FLTK event with the current script state, returns what fuse_input would.
+    pub fn send(&mut self, state: &mut EguiState, event: enums::Event) -> bool {
+        state.fuse_events(self, event)
+    }
+
+    /// Move the pointer, dragging if a button is held.
+    pub fn move_to(&mut self, state: &mut EguiState, x: i32, y: i32) -> bool {
+        self.coords = (x, y);
+        let event = if self.buttons_down.is_empty() {
+            enums::Event::Move
+        } else {
+            enums::Event::Drag
+        };
+        self.send(state, event)
+    }
+
+    /// Push a mouse button (1 left, 2 middle, 3 right, 4 back, 5 forward) at x, y.
+    pub fn push(&mut self, state: &mut EguiState, button: i32, x: i32, y: i32) -> bool {
+        self.coords = (x, y);
+        self.button = button;
+        self.buttons_down.push(button);
+        self.send(state, enums::Event::Push)
+    }
+
+    /// Release a mouse button at x, y.
+    pub fn release(&mut self, state: &mut EguiState, button: i32, x: i32, y: i32) -> bool {
+        self.coords = (x, y);
+        self.button = button;
+        self.buttons_down.retain(|b| *b != button);
+        self.send(state, enums::Event::Released)
+    }
+
+    /// Move to x, y and click the left button.
+    pub fn click(&mut self, state: &mut EguiState, x: i32, y: i32) {
+        self.move_to(state, x, y);
+        self.push(state, 1, x, y);
+        self.release(state, 1, x, y);
+    }
+
+    /// Scroll the mouse wheel, with the given modifiers held.
+    pub fn wheel(
+        &mut self,
+        state: &mut EguiState,
+        dy: app::MouseWheel,
+        modifiers: enums::EventState,
+    ) -> bool {
+        self.dy = dy;
+        self.state = modifiers;
+        let consumed = self.send(state, enums::Event::MouseWheel);
+        self.state = enums::EventState::None;
+        consumed
+    }
+
+    /// Press and release an FLTK key with the given modifiers held.
+    pub fn fltk_key(
+        &mut self,
+        state: &mut EguiState,
+        key: enums::Key,
+        text: &str,
+        modifiers: enums::EventState,
+    ) {
+        self.key = key;
+        self.text = text.to_string();
+        self.state = modifiers;
+        self.send(state, enums::Event::KeyDown);
+        self.text.clear();
+        self.send(state, enums::Event::KeyUp);
+        self.state = enums::EventState::None;
+    }
+
+    /// Press and release the key of a character, e.g. `key(state, 'c', EventState::Ctrl)`.
+    pub fn key(&mut self, state: &mut EguiState, c: char, modifiers: enums::EventState) {
+        let has_text = modifiers & (enums::EventState::Ctrl | enums::EventState::Alt)
+            == enums::EventState::None;
+        let text = if has_text {
+            c.to_string()
+        } else {
+            String::new()
+        };
+        self.fltk_key(state, enums::Key::from_char(c), &text, modifiers);
+    }
+
+    /// Type some text, one key press per character.
+    pub fn type_text(&mut self, state: &mut EguiState, text: &str) {
+        for c in text.chars() {
+            let shift = if c.is_uppercase() {
+                enums::EventState::Shift
+            } else {
+                enums::EventState::None
+            };
+            self.fltk_key(
+                state,
+                enums::Key::from_char(c.to_ascii_lowercase()),
+                &c.to_string(),
+                shift,
+            );
+        }
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn event_coords(&self) -> (i32, i32) {
+        self.coords
+    }
+
+    fn window_size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    fn event_button(&self) -> i32 {
+        self.button
+    }
+
+    fn event_key(&self) -> enums::Key {
+        self.key
+    }
+
+    fn event_text(&self) -> String {
+        self.text.clone()
+    }
+
+    fn event_state(&self) -> enums::EventState {
+        self.state
+    }
+
+    fn event_dy(&self) -> app::MouseWheel {
+        self.dy
+    }
+
+    fn compose(&mut self) -> Option<i32> {
+        if self.text.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
+    fn compose_reset(&mut self) {}
+
+    fn focus_neighbour(&mut self, _backward: bool) -> bool {
+        self.focus_handoffs += 1;
+        true
+    }
+
+    fn take_focus_later(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EguiInterest;
+    use egui::PointerButton;
+
+    fn buttons(input: &egui::RawInput) -> Vec<(PointerButton, bool)> {
+        input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::PointerButton {
+                    button, pressed, ..
+                } => Some((*button, *pressed)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn click_before_first_frame_is_taken() {
+        let mut state = EguiState::with_size([800, 600], 1.0, 2048);
+        let mut events = ScriptedEvents::new(800, 600);
+        events.move_to(&mut state, 10, 20);
+        assert!(events.push(&mut state, 1, 10, 20));
+        assert!(state.pointer_captured());
+        assert!(events.release(&mut state, 1, 10, 20));
+        assert!(!state.pointer_captured());
+        assert_eq!(
+            buttons(&state.take_input()),
+            [
+                (PointerButton::Primary, true),
+                (PointerButton::Primary, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn push_outside_egui_is_released_once() {
+        let mut state = EguiState::with_size([800, 600], 1.0, 2048);
+        // A frame ran and the pointer isn't over egui.
+        state.interest = EguiInterest::default();
+        let mut events = ScriptedEvents::new(800, 600);
+        assert!(!events.push(&mut state, 1, 10, 20));
+        assert!(!state.pointer_captured());
+        assert!(!events.release(&mut state, 1, 10, 20));
+        assert_eq!(
+            buttons(&state.take_input()),
+            [
+                (PointerButton::Primary, true),
+                (PointerButton::Primary, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn zoom_shortcut_sent_as_shortcut_zooms() {
+        let mut state = EguiState::with_size([800, 600], 1.0, 2048);
+        let mut events = ScriptedEvents::new(800, 600);
+        // No FLTK widget took the key, egui doesn't want the keyboard.
+        events.key = enums::Key::from_char('+');
+        events.state = enums::EventState::Command;
+        assert!(events.send(&mut state, enums::Event::Shortcut));
+        assert!(state.zoom() > 1.0);
+
+        events.key = enums::Key::from_char('0');
+        assert!(events.send(&mut state, enums::Event::Shortcut));
+        assert_eq!(state.zoom(), 1.0);
+
+        events.key = enums::Key::from_char('a');
+        assert!(!events.send(&mut state, enums::Event::Shortcut));
+        assert!(state.take_input().events.is_empty());
+    }
+
+    #[test]
+    fn typed_text_and_copy() {
+        let mut state = EguiState::with_size([800, 600], 1.0, 2048);
+        let mut events = ScriptedEvents::new(800, 600);
+        events.type_text(&mut state, "aB");
+        events.key(&mut state, 'c', enums::EventState::Command);
+        let input = state.take_input();
+        assert!(input.events.contains(&egui::Event::Text("a".into())));
+        assert!(input.events.contains(&egui::Event::Text("B".into())));
+        assert!(input.events.contains(&egui::Event::Key {
+            key: egui::Key::B,
+            pressed: true,
+            modifiers: egui::Modifiers {
+                shift: true,
+                ..Default::default()
+            },
+        }));
+        assert!(input.events.contains(&egui::Event::Copy));
+    }
+
+    #[test]
+    fn wheel_scrolls_and_zooms() {
+        let mut state = EguiState::with_size([800, 600], 1.0, 2048);
+        let mut events = ScriptedEvents::new(800, 600);
+        events.move_to(&mut state, 10, 20);
+        assert!(events.wheel(&mut state, app::MouseWheel::Down, enums::EventState::None));
+        assert!(events.wheel(&mut state, app::MouseWheel::Up, enums::EventState::Ctrl));
+        let input = state.take_input();
+        assert_eq!(
+            input.events[1..],
+            [
+                egui::Event::Scroll(egui::vec2(0.0, 12.0)),
+                egui::Event::Zoom((-8.0f32 / 200.0).exp()),
+            ]
+        );
+
+        state.zoom_settings.wheel = crate::ZoomGesture::Ui;
+        events.wheel(&mut state, app::MouseWheel::Up, enums::EventState::Ctrl);
+        assert!(state.zoom() > 1.0);
+        assert!(state.take_input().events.is_empty());
+    }
+
+    #[test]
+    fn drag_outside_is_captured_until_release() {
+        let mut state = EguiState::with_size([800, 600], 1.0, 2048);
+        let mut events = ScriptedEvents::new(800, 600);
+        events.move_to(&mut state, 10, 20);
+        assert!(events.push(&mut state, 1, 10, 20));
+        assert!(events.move_to(&mut state, -30, 700));
+        assert!(state.pointer_captured());
+        assert!(events.release(&mut state, 1, -30, 700));
+        assert!(!state.pointer_captured());
+        // Outside of the window and not dragging anymore.
+        assert!(!events.move_to(&mut state, -40, 700));
+
+        let input = state.take_input();
+        assert_eq!(
+            input.events,
+            [
+                egui::Event::PointerMoved(egui::pos2(10.0, 20.0)),
+                egui::Event::PointerButton {
+                    pos: egui::pos2(10.0, 20.0),
+                    button: PointerButton::Primary,
+                    pressed: true,
+                    modifiers: Default::default(),
+                },
+                egui::Event::PointerMoved(egui::pos2(-30.0, 700.0)),
+                egui::Event::PointerButton {
+                    pos: egui::pos2(-30.0, 700.0),
+                    button: PointerButton::Primary,
+                    pressed: false,
+                    modifiers: Default::default(),
+                },
+                egui::Event::PointerGone,
+            ]
+        );
+    }
+}
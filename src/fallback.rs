@@ -0,0 +1,261 @@
+use crate::{BackendBuilder, EguiState, SoftwarePainter};
+use egui::{ClippedPrimitive, ColorImage, TexturesDelta};
+use egui_glow::{glow, Painter};
+use fltk::{
+    app,
+    enums::Event,
+    prelude::{GroupExt, WidgetBase, WidgetExt, WindowExt},
+    window::{GlWindow, Window},
+};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+/// The painter `BackendBuilder::build_with_fallback` ended up with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PainterBackend {
+    /// egui_glow, on the GlWindow's OpenGL context.
+    Glow,
+    /// SoftwarePainter, blitted with FLTK's 2D drawing.
+    Software,
+}
+
+enum Inner {
+    /// The GlWindow is kept to make its context current when the painter is dropped.
+    Glow(Painter, GlWindow),
+    /// Shared with the draw callback of the canvas, see `software_canvas`.
+    Software(Rc<RefCell<SoftwarePainter>>, Canvas),
+}
+
+/// The plain window the SoftwarePainter's frame is blitted into.
+enum Canvas {
+    /// Added to the GlWindow and covering it.
+    Child(Window),
+    /// Shown in place of a GlWindow that couldn't be shown.
+    TopLevel(Window),
+}
+
+impl Canvas {
+    fn window(&mut self) -> &mut Window {
+        match self {
+            Canvas::Child(window) | Canvas::TopLevel(window) => window,
+        }
+    }
+}
+
+/// Paints with egui_glow, or with the SoftwarePainter when OpenGL isn't usable.
+///
+/// The egui_glow painter is destroyed on drop, with the GlWindow's context made current.
+pub struct FallbackPainter {
+    inner: Inner,
+    fallback_reason: Option<String>,
+}
+
+impl BackendBuilder {
+    /// Same as build(), falling back to the SoftwarePainter instead of panicking
+    /// when the egui_glow painter can't be created (no context, no GL 3, shader errors...).
+    ///
+    /// In that case the painted frame is blitted into a plain `Window` added to the
+    /// GlWindow and covering it, so that nothing is drawn through OpenGL. When the
+    /// GlWindow couldn't be shown at all (no GL visual), the plain window is shown in
+    /// its place instead, at the same position and with the same label. Pointer, keyboard
+    /// and focus events of that window are passed on to the GlWindow's handler.
+    /// Call `FallbackPainter::present` instead of `swap_buffers`,
+    /// `FallbackPainter::backend` tells which painter is used.
+    pub fn build_with_fallback(self, win: &mut GlWindow) -> (FallbackPainter, EguiState) {
+        match self.glow_painter(win) {
+            Ok(painter) => {
+                let state = self.state(win, painter.max_texture_side());
+                let painter = FallbackPainter {
+                    inner: Inner::Glow(painter, win.clone()),
+                    fallback_reason: None,
+                };
+                (painter, state)
+            }
+            Err(error) => {
+                eprintln!(
+                    "OpenGL painter unavailable ({}), painting on the CPU",
+                    error
+                );
+                let software = Rc::new(RefCell::new(SoftwarePainter::new()));
+                let canvas = software_canvas(win, &software);
+                let max_texture_side = software.borrow().max_texture_side();
+                let mut state = EguiState::with_size(
+                    [win.width() as u32, win.height() as u32],
+                    1.0,
+                    max_texture_side,
+                );
+                state.set_max_fps(self.max_fps);
+                let painter = FallbackPainter {
+                    inner: Inner::Software(software, canvas),
+                    fallback_reason: Some(error),
+                };
+                (painter, state)
+            }
+        }
+    }
+}
+
+impl FallbackPainter {
+    pub fn backend(&self) -> PainterBackend {
+        match self.inner {
+            Inner::Glow(..) => PainterBackend::Glow,
+            Inner::Software(..) => PainterBackend::Software,
+        }
+    }
+
+    /// Why the OpenGL painter couldn't be used, None when it is.
+    pub fn fallback_reason(&self) -> Option<&str> {
+        self.fallback_reason.as_deref()
+    }
+
+    /// The OpenGL context, None with the SoftwarePainter.
+    pub fn gl(&self) -> Option<&Arc<glow::Context>> {
+        match &self.inner {
+            Inner::Glow(painter, _) => Some(painter.gl()),
+            Inner::Software(..) => None,
+        }
+    }
+
+    pub fn max_texture_side(&self) -> usize {
+        match &self.inner {
+            Inner::Glow(painter, _) => painter.max_texture_side(),
+            Inner::Software(painter, _) => painter.borrow().max_texture_side(),
+        }
+    }
+
+    pub fn paint_and_update_textures(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clipped_primitives: &[ClippedPrimitive],
+        textures_delta: &TexturesDelta,
+    ) {
+        match &mut self.inner {
+            Inner::Glow(painter, _) => painter.paint_and_update_textures(
+                screen_size_px,
+                pixels_per_point,
+                clipped_primitives,
+                textures_delta,
+            ),
+            Inner::Software(painter, _) => painter.borrow_mut().paint_and_update_textures(
+                screen_size_px,
+                pixels_per_point,
+                clipped_primitives,
+                textures_delta,
+            ),
+        }
+    }
+
+    /// Reads back the last painted frame, see `capture`.
+    pub fn capture(&self, win: &GlWindow) -> ColorImage {
+        match &self.inner {
+            Inner::Glow(painter, _) => crate::capture(painter, win),
+            Inner::Software(painter, _) => painter.borrow().capture(),
+        }
+    }
+
+    /// Show the painted frame: swaps the buffers with OpenGL, schedules a redraw otherwise.
+    pub fn present(&mut self, win: &mut GlWindow) {
+        match &mut self.inner {
+            Inner::Glow(..) => win.swap_buffers(),
+            Inner::Software(_, canvas) => canvas.window().redraw(),
+        }
+    }
+}
+
+impl Drop for FallbackPainter {
+    fn drop(&mut self) {
+        match &mut self.inner {
+            Inner::Glow(painter, win) => {
+                // Otherwise the resources went away with the context (or the window).
+                if !win.was_deleted() && win.context().is_some() {
+                    win.make_current();
+                    painter.destroy();
+                }
+            }
+            Inner::Software(_, Canvas::TopLevel(canvas)) => {
+                if !canvas.was_deleted() {
+                    canvas.hide();
+                    Window::delete(canvas.clone());
+                }
+            }
+            // Deleted with the GlWindow.
+            Inner::Software(_, Canvas::Child(_)) => (),
+        }
+    }
+}
+
+/// A plain window blitting the software painter's frame: a subwindow filling `win`,
+/// or a top-level window in its place when `win` isn't shown.
+///
+/// It is a separate native window drawn with FLTK's 2D drawing: widgets inside a
+/// GlWindow are drawn through OpenGL.
+fn software_canvas(win: &mut GlWindow, software: &Rc<RefCell<SoftwarePainter>>) -> Canvas {
+    // Nothing of the GlWindow shows, don't let it draw with the broken GL.
+    win.draw(|_| {});
+    let top_level = !win.shown();
+    let mut canvas = if top_level {
+        let mut canvas = Window::new(win.x(), win.y(), win.width(), win.height(), None);
+        canvas.set_label(&win.label());
+        canvas
+    } else {
+        Window::new(0, 0, win.width(), win.height(), None)
+    };
+    canvas.end();
+    canvas.draw({
+        let software = software.clone();
+        move |_| software.borrow_mut().draw(0, 0)
+    });
+    canvas.handle({
+        let mut win = win.clone();
+        move |canvas, ev| match ev {
+            Event::Push => {
+                let taken = win.handle_event(ev);
+                // FLTK sends the key events to the focused window, the GlWindow can't
+                // take the focus when it isn't shown.
+                if taken && top_level {
+                    // Not from within its own handler.
+                    let mut canvas = canvas.clone();
+                    app::awake_callback(move || {
+                        if !canvas.was_deleted() {
+                            canvas.take_focus().ok();
+                        }
+                    });
+                }
+                taken
+            }
+            Event::Released
+            | Event::Drag
+            | Event::Move
+            | Event::MouseWheel
+            | Event::Enter
+            | Event::Leave
+            | Event::KeyDown
+            | Event::KeyUp
+            | Event::Shortcut
+            | Event::Focus
+            | Event::Unfocus
+            | Event::Paste => win.handle_event(ev),
+            _ => false,
+        }
+    });
+    if top_level {
+        // The GlWindow follows the canvas, for the event coordinates and the canvas size.
+        canvas.resize_callback({
+            let mut win = win.clone();
+            move |_, x, y, w, h| {
+                let resized = (w, h) != (win.width(), win.height());
+                win.resize(x, y, w, h);
+                if resized {
+                    win.handle_event(Event::Resize);
+                }
+            }
+        });
+        canvas.show();
+        Canvas::TopLevel(canvas)
+    } else {
+        win.add(&canvas);
+        win.resizable(&canvas);
+        canvas.show();
+        Canvas::Child(canvas)
+    }
+}
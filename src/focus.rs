@@ -0,0 +1,255 @@
+use egui::{Id, Key, Modifiers};
+use fltk::{
+    browser::Browser,
+    group::{Group, Tabs},
+    prelude::{GroupExt, WidgetBase, WidgetExt},
+    table::Table,
+    text::TextDisplay,
+    tree::Tree,
+    widget::Widget,
+};
+
+/// A Tab sent to egui, waiting to see where egui moved its focus.
+#[derive(Clone, Copy)]
+struct Tab {
+    backward: bool,
+    /// The widget that had focus when the key was sent.
+    before: Option<Id>,
+    /// Frames run since, egui moves the focus backward with a frame of delay.
+    frames: u8,
+}
+
+/// Moves keyboard focus between egui widgets and the native FLTK widgets around the GlWindow.
+///
+/// egui doesn't expose its focus order, so the first and last focusable egui widgets
+/// are learned while tabbing: passing the last one leaves egui with nothing focused,
+/// and shift-tabbing from the first one wraps around to the last one.
+pub(crate) struct FocusTraversal {
+    /// The GlWindow holds FLTK's keyboard focus, egui only keeps a focused widget while it does.
+    pub has_keyboard: bool,
+    /// egui's focused widget at the end of the last frame.
+    focused: Option<Id>,
+    /// Whether that widget locked the focus (e.g. a multiline text edit taking Tab).
+    locked: bool,
+    first: Option<Id>,
+    last: Option<Id>,
+    tab: Option<Tab>,
+    /// FLTK shift-tabbed into the GlWindow, egui has to focus its last widget.
+    enter_backward: bool,
+}
+
+impl Default for FocusTraversal {
+    fn default() -> Self {
+        Self {
+            has_keyboard: true,
+            focused: None,
+            locked: false,
+            first: None,
+            last: None,
+            tab: None,
+            enter_backward: false,
+        }
+    }
+}
+
+impl FocusTraversal {
+    /// FLTK tabbed into the GlWindow, returns the key egui needs to focus its first widget.
+    pub fn enter(&mut self, backward: bool) -> Option<egui::Event> {
+        self.has_keyboard = true;
+        self.enter_backward = backward;
+        if backward && self.last.is_some() {
+            // Focused directly in update().
+            return None;
+        }
+        self.tab = Some(Tab {
+            backward: false,
+            before: None,
+            frames: 0,
+        });
+        Some(tab_event(false))
+    }
+
+    /// A Tab key press is about to be sent to egui.
+    ///
+    /// Returns false if egui is at the edge of its focus order and the key has been
+    /// used to hand the focus to the neighbouring FLTK widget instead.
+    pub fn tab(&mut self, backward: bool, focus_neighbour: impl FnOnce(bool) -> bool) -> bool {
+        if self.locked {
+            return true;
+        }
+        let edge = if backward { self.first } else { self.last };
+        if self.focused.is_some() && self.focused == edge && focus_neighbour(backward) {
+            self.leave();
+            return false;
+        }
+        self.tab = Some(Tab {
+            backward,
+            before: self.focused,
+            frames: 0,
+        });
+        true
+    }
+
+    fn leave(&mut self) {
+        self.has_keyboard = false;
+        self.tab = None;
+        self.enter_backward = false;
+    }
+
+    /// Looks at egui's focus after a frame, returns the key to send with the next frame.
+    pub fn update<W: WidgetExt>(&mut self, win: &W, ctx: &egui::Context) -> Option<egui::Event> {
+        let mut memory = ctx.memory();
+        if !self.has_keyboard {
+            if let Some(id) = memory.focus() {
+                memory.surrender_focus(id);
+            }
+            self.focused = None;
+            return None;
+        }
+        if self.enter_backward {
+            if let Some(last) = self.last {
+                self.enter_backward = false;
+                memory.request_focus(last);
+            }
+        }
+        let focused = memory.focus();
+        self.locked = focused.map_or(false, |id| memory.has_lock_focus(id));
+        // The memory lock has to be released before requesting repaints.
+        drop(memory);
+
+        let mut event = None;
+        if let Some(mut tab) = self.tab.take() {
+            tab.frames += 1;
+            if !tab.backward && tab.before.is_some() && focused.is_none() {
+                // Tabbed past the last widget, egui is left with nothing focused.
+                self.last = tab.before;
+                if focus_fltk_neighbour(win, false) {
+                    self.leave();
+                    ctx.request_repaint();
+                }
+            } else if !tab.backward && tab.before.is_none() && focused.is_some() {
+                // Tabbed in from nothing, egui focused its first widget.
+                self.first = focused;
+                if self.enter_backward {
+                    // Shift+tab from the first widget wraps around to the last one.
+                    self.enter_backward = false;
+                    self.tab = Some(Tab {
+                        backward: true,
+                        before: focused,
+                        frames: 0,
+                    });
+                    event = Some(tab_event(true));
+                    ctx.request_repaint();
+                }
+            } else if tab.backward && focused != tab.before {
+                // Shift-tabbed from the first widget around to the last one.
+                if focused.is_some() && focused == self.last {
+                    self.first = tab.before;
+                    if focus_fltk_neighbour(win, true) {
+                        self.leave();
+                        ctx.request_repaint();
+                    }
+                }
+            } else if tab.backward && tab.frames < 2 {
+                self.tab = Some(tab);
+                ctx.request_repaint();
+            }
+        }
+        self.focused = focused;
+        event
+    }
+}
+
+fn tab_event(shift: bool) -> egui::Event {
+    egui::Event::Key {
+        key: Key::Tab,
+        pressed: true,
+        modifiers: Modifiers {
+            shift,
+            ..Default::default()
+        },
+    }
+}
+
+/// Gives the keyboard focus to the next (or previous) focusable FLTK widget around
+/// egui's widget (e.g. the GlWindow), in FLTK's tab order. Returns false when there is none.
+pub fn focus_fltk_neighbour<W: WidgetExt>(win: &W, backward: bool) -> bool {
+    let mut root = match win.parent() {
+        Some(parent) => parent,
+        None => return false,
+    };
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+
+    let mut widgets = Vec::new();
+    let mut anchor = None;
+    collect_focusable(&root, win, &mut widgets, &mut anchor);
+    let anchor = match anchor {
+        Some(anchor) => anchor,
+        None => return false,
+    };
+
+    // `anchor` is the index the GlWindow would have, i.e. that of the next widget.
+    let count = widgets.len();
+    for step in 0..count {
+        let idx = if backward {
+            (anchor + count - 1 - step) % count
+        } else {
+            (anchor + step) % count
+        };
+        if widgets[idx].take_focus().is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Flattens the widget tree in tab order, `anchor` being where the GlWindow sits in it.
+fn collect_focusable<W: WidgetExt>(
+    group: &Group,
+    win: &W,
+    widgets: &mut Vec<Widget>,
+    anchor: &mut Option<usize>,
+) {
+    for i in 0..group.children() {
+        let child = match group.child(i) {
+            Some(child) => child,
+            None => continue,
+        };
+        if !child.visible() || !child.active() {
+            continue;
+        }
+        if child.as_widget_ptr() == win.as_widget_ptr() {
+            *anchor = Some(widgets.len());
+            continue;
+        }
+        let focusable = child.has_visible_focus() && child.takes_events();
+        match child.as_group() {
+            // Its children (scrollbars, ...) are parts of it.
+            Some(_) if handles_keyboard(&child) => {
+                if focusable {
+                    widgets.push(child);
+                }
+            }
+            // Tabs take the focus to switch tabs with the arrow keys, before the tab's widgets.
+            Some(group) if Tabs::from_dyn_widget(&child).is_some() => {
+                if focusable {
+                    widgets.push(child);
+                }
+                collect_focusable(&group, win, widgets, anchor);
+            }
+            Some(group) => collect_focusable(&group, win, widgets, anchor),
+            None if focusable => widgets.push(child),
+            None => {}
+        }
+    }
+}
+
+/// Groups driven by the keyboard as a whole, focused rather than their children.
+fn handles_keyboard(group: &Widget) -> bool {
+    Browser::from_dyn_widget(group).is_some()
+        || TextDisplay::from_dyn_widget(group).is_some()
+        || Tree::from_dyn_widget(group).is_some()
+        || Table::from_dyn_widget(group).is_some()
+}
@@ -0,0 +1,240 @@
+use egui::{PaintCallback, Rect};
+use egui_glow::glow::{self, HasContext};
+use std::{cell::Cell, os::raw::c_void, sync::Arc};
+
+/// Where a glow paint callback paints, in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlViewport {
+    /// x, y (from the bottom, as `glViewport` expects), width and height of the
+    /// callback's rect, set as the viewport.
+    pub viewport: [i32; 4],
+    /// The visible part of it (e.g. inside a ScrollArea), set as the scissor box.
+    pub clip: [i32; 4],
+    pub pixels_per_point: f32,
+    /// Full size of the framebuffer.
+    pub screen_size_px: [u32; 2],
+    /// The framebuffer egui paints into, bound during the callback: None for the
+    /// window's own, Some when egui_glow paints into an intermediate one.
+    pub framebuffer: Option<glow::Framebuffer>,
+}
+
+/// A `PaintCallback` painting in `rect` with glow, to add with `ui.painter().add(...)`.
+///
+/// Before `paint`, the viewport and scissor box are set from the callback's rect and
+/// clip rect, the framebuffer egui paints into is bound and `FRAMEBUFFER_SRGB` is
+/// disabled. After it, the blend, depth, scissor, cull face and sRGB state, the
+/// viewport, color mask, active texture unit, program and vertex array are restored and
+/// egui's framebuffer is bound again. egui_glow binds its own buffers and textures after
+/// each callback, so `paint` can leave its own bound.
+///
+/// The program and vertex array are restored with the GL functions loaded by
+/// `BackendBuilder` and `OffscreenRenderer::new`; with a painter created otherwise
+/// (e.g. `OffscreenRenderer::with_gl`) they are left to egui_glow.
+///
+/// ```no_run
+/// # use fltk_egui::{egui, egui_glow::glow::{self, HasContext}, glow_callback};
+/// # fn ui(ui: &mut egui::Ui) {
+/// let (rect, _) = ui.allocate_exact_size(egui::vec2(200.0, 200.0), egui::Sense::hover());
+/// ui.painter().add(glow_callback(rect, |gl, _viewport| unsafe {
+///     gl.clear_color(0.1, 0.2, 0.3, 1.0);
+///     gl.clear(glow::COLOR_BUFFER_BIT);
+/// }));
+/// # }
+/// ```
+pub fn glow_callback(
+    rect: Rect,
+    paint: impl Fn(&Arc<glow::Context>, &GlViewport) + Send + Sync + 'static,
+) -> PaintCallback {
+    PaintCallback {
+        rect,
+        callback: Arc::new(egui_glow::CallbackFn::new(move |info, painter| {
+            let gl = painter.gl();
+            // epaint doesn't export the type of these.
+            let (viewport_px, clip_px) = (info.viewport_in_pixels(), info.clip_rect_in_pixels());
+            let viewport = GlViewport {
+                viewport: [
+                    viewport_px.left_px.round() as i32,
+                    viewport_px.from_bottom_px.round() as i32,
+                    viewport_px.width_px.round() as i32,
+                    viewport_px.height_px.round() as i32,
+                ],
+                clip: [
+                    clip_px.left_px.round() as i32,
+                    clip_px.from_bottom_px.round() as i32,
+                    clip_px.width_px.round() as i32,
+                    clip_px.height_px.round() as i32,
+                ],
+                pixels_per_point: info.pixels_per_point,
+                screen_size_px: info.screen_size_px,
+                framebuffer: painter.intermediate_fbo(),
+            };
+            unsafe {
+                let saved = GlState::save(gl);
+                gl.bind_framebuffer(glow::FRAMEBUFFER, viewport.framebuffer);
+                let [x, y, w, h] = viewport.viewport;
+                gl.viewport(x, y, w, h);
+                let [x, y, w, h] = viewport.clip;
+                gl.enable(glow::SCISSOR_TEST);
+                gl.scissor(x, y, w, h);
+                if !gl.version().is_embedded {
+                    gl.disable(glow::FRAMEBUFFER_SRGB);
+                }
+                paint(gl, &viewport);
+                saved.restore(gl);
+                gl.bind_framebuffer(glow::FRAMEBUFFER, viewport.framebuffer);
+            }
+        })),
+    }
+}
+
+/// Raw `glUseProgram` and `glBindVertexArray`, to bind the names read back with
+/// `get_parameter_i32`: glow has no way to make its handles from them.
+#[derive(Clone, Copy)]
+struct BindFns {
+    use_program: unsafe extern "system" fn(u32),
+    bind_vertex_array: Option<unsafe extern "system" fn(u32)>,
+}
+
+thread_local! {
+    /// Loaded with the context, paint callbacks run on its thread.
+    static BIND_FNS: Cell<Option<BindFns>> = Cell::new(None);
+}
+
+/// Load the functions restoring the program and vertex array after a paint callback,
+/// with the context of `loader` current.
+pub(crate) fn load_bind_fns(gl: &glow::Context, mut loader: impl FnMut(&str) -> *const c_void) {
+    let version = gl.version();
+    // glXGetProcAddress returns an address for any name, check the context has them.
+    let has_vertex_arrays = version.major >= 3
+        || gl
+            .supported_extensions()
+            .contains("GL_ARB_vertex_array_object")
+        || gl
+            .supported_extensions()
+            .contains("GL_OES_vertex_array_object");
+    let use_program = loader("glUseProgram");
+    let bind_vertex_array = if !has_vertex_arrays {
+        std::ptr::null()
+    } else if version.is_embedded && version.major < 3 {
+        loader("glBindVertexArrayOES")
+    } else {
+        loader("glBindVertexArray")
+    };
+    let fns = unsafe {
+        if use_program.is_null() {
+            None
+        } else {
+            Some(BindFns {
+                use_program: std::mem::transmute(use_program),
+                bind_vertex_array: if bind_vertex_array.is_null() {
+                    None
+                } else {
+                    Some(std::mem::transmute(bind_vertex_array))
+                },
+            })
+        }
+    };
+    BIND_FNS.with(|bind_fns| bind_fns.set(fns));
+}
+
+/// The GL state a paint callback is likely to change, that egui_glow doesn't set again.
+struct GlState {
+    blend: bool,
+    blend_func: [i32; 4],
+    blend_equation: [i32; 2],
+    depth_test: bool,
+    depth_mask: bool,
+    depth_func: i32,
+    scissor_test: bool,
+    scissor_box: [i32; 4],
+    cull_face: bool,
+    /// None on GL ES, which has no such switch.
+    framebuffer_srgb: Option<bool>,
+    viewport: [i32; 4],
+    color_mask: [i32; 4],
+    active_texture: i32,
+    /// With the functions to bind them again, None if they aren't loaded.
+    program: Option<(BindFns, u32, Option<u32>)>,
+}
+
+impl GlState {
+    unsafe fn save(gl: &glow::Context) -> Self {
+        let slice = |parameter| {
+            let mut values = [0; 4];
+            gl.get_parameter_i32_slice(parameter, &mut values);
+            values
+        };
+        Self {
+            blend: gl.is_enabled(glow::BLEND),
+            blend_func: [
+                gl.get_parameter_i32(glow::BLEND_SRC_RGB),
+                gl.get_parameter_i32(glow::BLEND_DST_RGB),
+                gl.get_parameter_i32(glow::BLEND_SRC_ALPHA),
+                gl.get_parameter_i32(glow::BLEND_DST_ALPHA),
+            ],
+            blend_equation: [
+                gl.get_parameter_i32(glow::BLEND_EQUATION_RGB),
+                gl.get_parameter_i32(glow::BLEND_EQUATION_ALPHA),
+            ],
+            depth_test: gl.is_enabled(glow::DEPTH_TEST),
+            depth_mask: gl.get_parameter_i32(glow::DEPTH_WRITEMASK) != 0,
+            depth_func: gl.get_parameter_i32(glow::DEPTH_FUNC),
+            scissor_test: gl.is_enabled(glow::SCISSOR_TEST),
+            scissor_box: slice(glow::SCISSOR_BOX),
+            cull_face: gl.is_enabled(glow::CULL_FACE),
+            framebuffer_srgb: if gl.version().is_embedded {
+                None
+            } else {
+                Some(gl.is_enabled(glow::FRAMEBUFFER_SRGB))
+            },
+            viewport: slice(glow::VIEWPORT),
+            color_mask: slice(glow::COLOR_WRITEMASK),
+            active_texture: gl.get_parameter_i32(glow::ACTIVE_TEXTURE),
+            program: BIND_FNS.with(Cell::get).map(|fns| {
+                let program = gl.get_parameter_i32(glow::CURRENT_PROGRAM) as u32;
+                let vertex_array = fns
+                    .bind_vertex_array
+                    .map(|_| gl.get_parameter_i32(glow::VERTEX_ARRAY_BINDING) as u32);
+                (fns, program, vertex_array)
+            }),
+        }
+    }
+
+    unsafe fn restore(&self, gl: &glow::Context) {
+        let set = |capability, enabled| {
+            if enabled {
+                gl.enable(capability);
+            } else {
+                gl.disable(capability);
+            }
+        };
+        set(glow::BLEND, self.blend);
+        let [src_rgb, dst_rgb, src_alpha, dst_alpha] = self.blend_func.map(|f| f as u32);
+        gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+        let [rgb, alpha] = self.blend_equation.map(|e| e as u32);
+        gl.blend_equation_separate(rgb, alpha);
+        set(glow::DEPTH_TEST, self.depth_test);
+        gl.depth_mask(self.depth_mask);
+        gl.depth_func(self.depth_func as u32);
+        set(glow::SCISSOR_TEST, self.scissor_test);
+        let [x, y, w, h] = self.scissor_box;
+        gl.scissor(x, y, w, h);
+        set(glow::CULL_FACE, self.cull_face);
+        if let Some(framebuffer_srgb) = self.framebuffer_srgb {
+            set(glow::FRAMEBUFFER_SRGB, framebuffer_srgb);
+        }
+        let [x, y, w, h] = self.viewport;
+        gl.viewport(x, y, w, h);
+        let [r, g, b, a] = self.color_mask.map(|mask| mask != 0);
+        gl.color_mask(r, g, b, a);
+        gl.active_texture(self.active_texture as u32);
+        if let Some((fns, program, vertex_array)) = self.program {
+            (fns.use_program)(program);
+            if let (Some(bind_vertex_array), Some(vertex_array)) =
+                (fns.bind_vertex_array, vertex_array)
+            {
+                bind_vertex_array(vertex_array);
+            }
+        }
+    }
+}
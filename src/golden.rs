@@ -0,0 +1,265 @@
+use crate::OffscreenRenderer;
+use egui::{Color32, ColorImage};
+use fltk::{enums::ColorDepth, image::SharedImage, prelude::ImageExt};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Set to a non-empty value to (re)write the golden images instead of comparing with them.
+pub const UPDATE_GOLDEN_ENV: &str = "FLTK_EGUI_UPDATE_GOLDEN";
+
+/// Golden-image tests: renders egui with an `OffscreenRenderer` and compares the result
+/// with an image stored in `dir`.
+///
+/// Every check starts from a new egui context (with the fonts and style set on
+/// `renderer().context()`, no input, clock at 0) and paints over an opaque background,
+/// so the same closure gives the
+/// same image on the same renderer. Different GL drivers antialias a bit differently,
+/// allow for it with `tolerance` and `max_differing_pixels`.
+///
+/// ```no_run
+/// use fltk_egui::{egui, GoldenImages, OffscreenRenderer};
+///
+/// let mut golden = GoldenImages::new(OffscreenRenderer::new().unwrap(), "tests/golden");
+/// golden.assert_matches("heading", |ctx| {
+///     egui::CentralPanel::default().show(ctx, |ui| ui.heading("Hello"));
+/// });
+/// ```
+///
+/// Golden images can be PNG or PPM files named after the check (`heading.png`, or
+/// `heading.ppm`). A missing one fails the check, they are written as PPM when the
+/// `FLTK_EGUI_UPDATE_GOLDEN` environment variable is set. On failure, the rendered
+/// image and a diff image (differing pixels in red over the faded expected image) are
+/// written next to it as `heading.actual.ppm` and `heading.diff.ppm`.
+pub struct GoldenImages {
+    renderer: OffscreenRenderer,
+    dir: PathBuf,
+    /// Size of the rendered images in pixels, default value is 320x240.
+    pub size: [u32; 2],
+    /// Default value is 1.0.
+    pub pixels_per_point: f32,
+    /// Largest difference of a channel for pixels to still match, default value is 2.
+    pub tolerance: u8,
+    /// How many pixels may differ by more than `tolerance`, default value is 0.
+    pub max_differing_pixels: usize,
+}
+
+impl GoldenImages {
+    pub fn new(mut renderer: OffscreenRenderer, dir: impl Into<PathBuf>) -> Self {
+        renderer.clear_color = Color32::from_gray(27);
+        Self {
+            renderer,
+            dir: dir.into(),
+            size: [320, 240],
+            pixels_per_point: 1.0,
+            tolerance: 2,
+            max_differing_pixels: 0,
+        }
+    }
+
+    /// The renderer, e.g. to change its clear color or number of frames.
+    pub fn renderer(&mut self) -> &mut OffscreenRenderer {
+        &mut self.renderer
+    }
+
+    /// Render `ui` and compare it with the golden image `name`.
+    pub fn check(&mut self, name: &str, ui: impl FnMut(&egui::Context)) -> Result<(), String> {
+        self.renderer.reset();
+        let actual = self.renderer.render(self.size, self.pixels_per_point, ui);
+
+        let stored = ["png", "ppm"]
+            .iter()
+            .map(|ext| self.dir.join(format!("{}.{}", name, ext)))
+            .find(|path| path.exists());
+        let update = std::env::var_os(UPDATE_GOLDEN_ENV).map_or(false, |v| !v.is_empty());
+        let expected_path = match stored {
+            Some(path) if !update => path,
+            None if !update => {
+                return Err(format!(
+                    "golden image {} is missing in {}, set {}=1 to write it",
+                    name,
+                    self.dir.display(),
+                    UPDATE_GOLDEN_ENV
+                ))
+            }
+            _ => {
+                let path = self.dir.join(format!("{}.ppm", name));
+                fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+                save_ppm(&actual, &path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                eprintln!("wrote golden image {}", path.display());
+                return Ok(());
+            }
+        };
+
+        let expected = load_image(&expected_path)?;
+        let failure = if expected.size != actual.size {
+            Some(format!(
+                "size {:?} differs from {:?} of {}",
+                actual.size,
+                expected.size,
+                expected_path.display()
+            ))
+        } else {
+            let diff = compare_images(&actual, &expected, self.tolerance);
+            if diff.differing_pixels > self.max_differing_pixels {
+                let path = self.dir.join(format!("{}.diff.ppm", name));
+                save_ppm(&diff.image, &path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                Some(format!(
+                    "{} pixels differ from {} (by up to {}), see {}",
+                    diff.differing_pixels,
+                    expected_path.display(),
+                    diff.max_difference,
+                    path.display()
+                ))
+            } else {
+                None
+            }
+        };
+
+        match failure {
+            Some(failure) => {
+                let path = self.dir.join(format!("{}.actual.ppm", name));
+                save_ppm(&actual, &path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                Err(format!("golden image {}: {}", name, failure))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Same as check(), panicking on mismatch.
+    pub fn assert_matches(&mut self, name: &str, ui: impl FnMut(&egui::Context)) {
+        if let Err(error) = self.check(name, ui) {
+            panic!("{}", error);
+        }
+    }
+}
+
+/// Result of `compare_images`.
+pub struct ImageDiff {
+    /// Pixels with a channel differing by more than the tolerance.
+    pub differing_pixels: usize,
+    /// Largest difference of a channel, over all pixels.
+    pub max_difference: u8,
+    /// The differing pixels in red over the faded expected image.
+    pub image: ColorImage,
+}
+
+/// Compare two images of the same size, channel by channel.
+pub fn compare_images(actual: &ColorImage, expected: &ColorImage, tolerance: u8) -> ImageDiff {
+    assert_eq!(actual.size, expected.size, "images of different sizes");
+    let mut differing_pixels = 0;
+    let mut max_difference = 0;
+    let pixels = actual
+        .pixels
+        .iter()
+        .zip(&expected.pixels)
+        .map(|(a, e)| {
+            let difference = (0..4).map(|i| a[i].abs_diff(e[i])).max().unwrap_or(0);
+            max_difference = max_difference.max(difference);
+            if difference > tolerance {
+                differing_pixels += 1;
+                Color32::RED
+            } else {
+                let [r, g, b, _] = e.to_array();
+                let gray = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+                Color32::from_gray(gray / 4 + 96)
+            }
+        })
+        .collect();
+    ImageDiff {
+        differing_pixels,
+        max_difference,
+        image: ColorImage {
+            size: actual.size,
+            pixels,
+        },
+    }
+}
+
+/// Load an image with FLTK (PNG, PPM, JPEG, BMP...), e.g. a golden image.
+pub fn load_image(path: impl AsRef<Path>) -> Result<ColorImage, String> {
+    let path = path.as_ref();
+    let image = SharedImage::load(path)
+        .and_then(|image| image.to_rgb())
+        .and_then(|image| image.convert(ColorDepth::Rgba8))
+        .map_err(|e| format!("{}: {:?}", path.display(), e))?;
+    Ok(ColorImage::from_rgba_unmultiplied(
+        [image.data_w() as usize, image.data_h() as usize],
+        &image.to_rgb_data(),
+    ))
+}
+
+/// Save an image as binary PPM, without alpha (blended over black).
+pub fn save_ppm(image: &ColorImage, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", image.size[0], image.size[1])?;
+    for pixel in &image.pixels {
+        // Premultiplied, so this is over black.
+        file.write_all(&pixel.to_array()[..3])?;
+    }
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(pixels: &[Color32]) -> ColorImage {
+        ColorImage {
+            size: [pixels.len(), 1],
+            pixels: pixels.to_vec(),
+        }
+    }
+
+    #[test]
+    fn compare_within_tolerance() {
+        let expected = image(&[Color32::from_rgb(10, 20, 30), Color32::WHITE]);
+        let actual = image(&[Color32::from_rgb(12, 18, 30), Color32::from_gray(250)]);
+
+        let diff = compare_images(&actual, &expected, 2);
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.max_difference, 5);
+        assert_eq!(diff.image.size, [2, 1]);
+        assert_ne!(diff.image.pixels[0], Color32::RED);
+        assert_eq!(diff.image.pixels[1], Color32::RED);
+
+        let diff = compare_images(&actual, &expected, 5);
+        assert_eq!(diff.differing_pixels, 0);
+        assert_eq!(compare_images(&expected, &expected, 0).max_difference, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "images of different sizes")]
+    fn compare_different_sizes() {
+        compare_images(&image(&[Color32::BLACK]), &image(&[Color32::BLACK; 2]), 255);
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let pixels: Vec<_> = (0..12)
+            .map(|i| Color32::from_rgb(i * 20, 255 - i * 20, i))
+            .collect();
+        let original = ColorImage {
+            size: [4, 3],
+            pixels,
+        };
+        let path = std::env::temp_dir().join(format!("fltk-egui-{}.ppm", std::process::id()));
+        save_ppm(&original, &path).unwrap();
+        let loaded = load_image(&path);
+        fs::remove_file(&path).ok();
+        // ColorImage isn't Debug.
+        assert!(loaded.unwrap() == original);
+    }
+
+    #[cfg(feature = "software")]
+    #[test]
+    fn missing_golden_fails() {
+        let dir = std::env::temp_dir().join(format!("fltk-egui-golden-{}", std::process::id()));
+        let mut golden = GoldenImages::new(OffscreenRenderer::software(), &dir);
+        let error = golden.check("missing", |_| {}).unwrap_err();
+        assert!(error.contains(UPDATE_GOLDEN_ENV), "{}", error);
+        assert!(!dir.join("missing.ppm").exists());
+    }
+}
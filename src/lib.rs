@@ -4,65 +4,199 @@
 use std::{sync::Arc, time::Instant};
 
 // Re-export dependencies.
+pub use backend::GlBackend;
+pub use capture::{capture, capture_rgb_image};
 pub use egui;
-use egui::{pos2, vec2, CursorIcon, Event, Key, Modifiers, Pos2, RawInput, Rect, Vec2};
+use egui::{pos2, vec2, Event, Key, Modifiers, Pos2, RawInput, Rect, Vec2};
 pub use egui_glow;
 use egui_glow::{glow, Painter};
 pub use egui_image::RetainedEguiImage;
+#[cfg(feature = "software")]
+pub use fallback::{FallbackPainter, PainterBackend};
 pub use fltk;
 use fltk::{
     app, enums,
     prelude::{FltkError, ImageExt, WidgetExt, WindowExt},
     window::GlWindow,
 };
+pub use gl_callback::{glow_callback, GlViewport};
+pub use golden::{
+    compare_images, load_image, save_ppm, GoldenImages, ImageDiff, UPDATE_GOLDEN_ENV,
+};
+pub use offscreen::OffscreenRenderer;
+pub use open_url::{open_url_with_system, UrlHandler};
+pub use print::{print, PrintScale};
+pub use recording::{InputRecorder, InputReplayer, RecordedFrame};
+pub use shader::ShaderVersion;
+#[cfg(feature = "software")]
+pub use software::{with_software, SoftwarePainter};
+pub use stats::{FramePhase, FrameStats};
+pub use vector::VectorPainter;
 
+mod backend;
+mod capture;
 mod clipboard;
+mod clock;
+mod cursor;
 mod egui_image;
+mod event_source;
+#[cfg(feature = "software")]
+mod fallback;
+mod focus;
+mod gl_callback;
+mod golden;
+mod offscreen;
+mod open_url;
+mod pacing;
+mod postscript;
+mod print;
+mod recording;
+mod shader;
+#[cfg(feature = "software")]
+mod software;
+mod stats;
+mod vector;
+mod zoom;
 use clipboard::Clipboard;
+use clock::FrameClock;
+pub use cursor::{builtin_cursor_image, fltk_cursor, translate_cursor, CursorImage, FusedCursor};
+pub use event_source::{EventSource, FltkEvents, ScriptedEvents};
+pub use focus::focus_fltk_neighbour;
+use focus::FocusTraversal;
+pub use pacing::set_swap_interval;
+use pacing::FramePacer;
+use stats::FrameStatsCollector;
+use zoom::{zoom_shortcut, ZoomShortcut};
+pub use zoom::{ZoomGesture, ZoomSettings};
 
 /// Construct the backend.
 pub fn with_fltk(win: &mut GlWindow) -> (Painter, EguiState) {
-    app::set_screen_scale(win.screen_num(), 1.);
-    app::keyboard_screen_scaling(false);
-    let gl = unsafe { glow::Context::from_loader_function(|s| win.get_proc_address(s) as _) };
-    let painter = Painter::new(Arc::from(gl), None, "")
-        .unwrap_or_else(|error| panic!("some OpenGL error occurred {}\n", error));
-    let max_texture_side = painter.max_texture_side();
-    (painter, EguiState::new(&win, max_texture_side))
+    BackendBuilder::new().build(win)
 }
 
-/// Frame time for FPS.
-pub fn get_frame_time(start_time: Instant) -> f32 {
-    (Instant::now() - start_time).as_secs_f64() as f32
+/// Construct the backend with options, e.g:
+///
+/// ```no_run
+/// # use fltk_egui::{fltk::window::GlWindow, BackendBuilder};
+/// # fn init(win: &mut GlWindow) {
+/// let (painter, state) = BackendBuilder::new().vsync(true).max_fps(60.0).build(win);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BackendBuilder {
+    vsync: Option<bool>,
+    max_fps: Option<f32>,
+    shader_version: Option<ShaderVersion>,
+    shader_prefix: String,
 }
 
-/// Casting slice to another type of slice
-pub fn cast_slice<T, D>(s: &[T]) -> &[D] {
-    unsafe {
-        std::slice::from_raw_parts(s.as_ptr() as *const D, s.len() * std::mem::size_of::<T>())
+impl BackendBuilder {
+    pub fn new() -> Self {
+        Self::default()
     }
-}
 
-/// The default cursor
-pub struct FusedCursor {
-    pub cursor_icon: fltk::enums::Cursor,
-}
+    /// Turn vsync on or off, left to the driver's default if not set. See [`set_swap_interval`].
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = Some(vsync);
+        self
+    }
 
-const ARROW: enums::Cursor = enums::Cursor::Arrow;
+    /// Run at most `max_fps` frames per second.
+    ///
+    /// The cap is kept by the event loop, not by painting: skip the frame while
+    /// `EguiState::frame_due` returns false, as in the demo_windows example.
+    pub fn max_fps(mut self, max_fps: f32) -> Self {
+        self.max_fps = Some(max_fps);
+        self
+    }
 
-impl FusedCursor {
-    /// Construct a new cursor
-    pub fn new() -> Self {
-        Self { cursor_icon: ARROW }
+    /// Paint with `version` shaders, derived from the GlWindow's mode if not set
+    /// (see [`ShaderVersion::from_mode`]).
+    ///
+    /// The window's mode is changed to get a context of that version. When the driver
+    /// provides another one, a warning is printed and egui_glow paints with the shaders
+    /// of the context it got.
+    pub fn shader_version(mut self, version: ShaderVersion) -> Self {
+        self.shader_version = Some(version);
+        self
+    }
+
+    /// Prepended to egui's shaders after the `#version` line, e.g.
+    /// `"#define APPLY_BRIGHTENING_GAMMA\n"` for drivers with wrong gamma.
+    pub fn shader_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.shader_prefix = prefix.into();
+        self
+    }
+
+    /// Construct the painter and the state of a shown GlWindow.
+    pub fn build(self, win: &mut GlWindow) -> (Painter, EguiState) {
+        let painter = self
+            .glow_painter(win)
+            .unwrap_or_else(|error| panic!("some OpenGL error occurred {}\n", error));
+        let state = self.state(win, painter.max_texture_side());
+        (painter, state)
+    }
+
+    /// The egui_glow painter of the GlWindow, with the shader and vsync options applied.
+    fn glow_painter(&self, win: &mut GlWindow) -> Result<Painter, String> {
+        app::set_screen_scale(win.screen_num(), 1.);
+        app::keyboard_screen_scaling(false);
+        let wanted = self
+            .shader_version
+            .unwrap_or_else(|| ShaderVersion::from_mode(win.mode()));
+        let mode = wanted.apply_to_mode(win.mode());
+        if mode != win.mode() {
+            // FLTK recreates the context of a shown window.
+            win.set_mode(mode);
+        }
+        win.make_current();
+        // The GL functions can't be loaded (and called) without a context.
+        if win.context().is_none() {
+            return Err("no OpenGL context".to_string());
+        }
+        let gl = unsafe { glow::Context::from_loader_function(|s| win.get_proc_address(s) as _) };
+        gl_callback::load_bind_fns(&gl, |s| win.get_proc_address(s) as _);
+        let version = ShaderVersion::detect(&gl);
+        if !version.supports(wanted) {
+            eprintln!(
+                "{:?} shaders requested, painting with the {:?} ones of the OpenGL context",
+                wanted, version
+            );
+        }
+        let painter = Painter::new(Arc::from(gl), None, &self.shader_prefix)?;
+        if let Some(vsync) = self.vsync {
+            if !set_swap_interval(win, vsync as i32) {
+                eprintln!("Failed to turn vsync {}", if vsync { "on" } else { "off" });
+            }
+        }
+        Ok(painter)
+    }
+
+    fn state(&self, win: &GlWindow, max_texture_side: usize) -> EguiState {
+        let mut state = EguiState::new(win, max_texture_side);
+        state.set_max_fps(self.max_fps);
+        state
     }
 }
 
-impl Default for FusedCursor {
-    fn default() -> Self {
-        Self::new()
+/// Casting slice to another type of slice
+pub fn cast_slice<T, D>(s: &[T]) -> &[D] {
+    unsafe {
+        std::slice::from_raw_parts(s.as_ptr() as *const D, s.len() * std::mem::size_of::<T>())
     }
 }
 
+/// What egui was interested in at the end of the last frame, see `EguiState::update_interest`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EguiInterest {
+    /// egui is listening on text input (e.g. a focused TextEdit).
+    pub wants_keyboard_input: bool,
+    /// egui is using the pointer, or it is hovering an egui area.
+    pub wants_pointer_input: bool,
+    /// The pointer is over an egui area (panel, window, ...).
+    pub pointer_over_area: bool,
+}
+
 /// Shuttles FLTK's input and events to Egui
 pub struct EguiState {
     pub canvas_size: [u32; 2],
@@ -71,25 +205,58 @@ pub struct EguiState {
     /// Use state.input.take() use this fn instead (to avoid pixels per point miscalculation).
     pub input: RawInput,
     _pixels_per_point: f32,
+    /// The scale set with set_visual_scale(), the zoom applies on top of it.
+    visual_scale: f32,
+    zoom: f32,
+    pub zoom_settings: ZoomSettings,
     pub pointer_pos: Pos2,
     /// default value is 12.0
     pub scroll_factor: f32,
-    /// default value is 8.0
+    /// Used by ZoomGesture::Event, default value is 8.0
     pub zoom_factor: f32,
+    /// Merge consecutive pointer motion into a single PointerMoved per frame, default value is false.
+    pub coalesce_pointer_motion: bool,
+    /// Every pointer position received since the last take_input().
+    pending_pointer_samples: Vec<Pos2>,
+    /// Every pointer position that led to the last taken input.
+    pointer_samples: Vec<Pos2>,
     /// Internal use case for fn window_resized()
     _window_resized: bool,
+    /// Buttons pressed inside the window whose release hasn't been seen yet.
+    captured_buttons: Vec<egui::PointerButton>,
+    /// egui was last told the pointer is over the window.
+    pointer_in_window: bool,
     pub max_texture_side: usize,
+    url_handler: UrlHandler,
+    interest: EguiInterest,
+    focus: FocusTraversal,
+    recorder: Option<InputRecorder>,
+    clock: FrameClock,
+    stats: FrameStatsCollector,
+    pacer: FramePacer,
 }
 
 impl EguiState {
     /// Construct a new state
     pub fn new(win: &GlWindow, max_texture_side: usize) -> EguiState {
-        let ppu = win.pixels_per_unit();
-        let (width, height) = (win.width(), win.height());
-        let rect = vec2(width as f32, height as f32) / ppu;
+        Self::with_size(
+            [win.width() as u32, win.height() as u32],
+            win.pixels_per_unit(),
+            max_texture_side,
+        )
+    }
+
+    /// Construct a new state without a window, e.g. to feed it `ScriptedEvents` in tests.
+    pub fn with_size(
+        canvas_size: [u32; 2],
+        pixels_per_point: f32,
+        max_texture_side: usize,
+    ) -> EguiState {
+        let ppu = pixels_per_point;
+        let rect = vec2(canvas_size[0] as f32, canvas_size[1] as f32) / ppu;
         let screen_rect = Rect::from_min_size(Pos2::new(0f32, 0f32), rect);
         EguiState {
-            canvas_size: [width as u32, height as u32],
+            canvas_size,
             clipboard: Clipboard::default(),
             fuse_cursor: FusedCursor::new(),
             input: egui::RawInput {
@@ -100,24 +267,165 @@ impl EguiState {
             },
             max_texture_side,
             _pixels_per_point: ppu,
+            visual_scale: ppu,
+            zoom: 1.0,
+            zoom_settings: ZoomSettings::default(),
             pointer_pos: Pos2::new(0f32, 0f32),
             scroll_factor: 12.0,
             zoom_factor: 8.0,
+            coalesce_pointer_motion: false,
+            pending_pointer_samples: Vec::new(),
+            pointer_samples: Vec::new(),
             _window_resized: false,
+            captured_buttons: Vec::new(),
+            pointer_in_window: false,
+            url_handler: open_url::default_url_handler(),
+            // Until the first frame, a click may well be meant for egui: taking it keeps
+            // its release and drags coming.
+            interest: EguiInterest {
+                wants_pointer_input: true,
+                ..Default::default()
+            },
+            focus: FocusTraversal::default(),
+            recorder: None,
+            clock: FrameClock::default(),
+            stats: FrameStatsCollector::default(),
+            pacer: FramePacer::default(),
         }
     }
 
+    /// Take the input of the next frame.
+    ///
+    /// `time` is filled from the state's clock unless it was set by hand,
+    /// and `predicted_dt` is estimated from the recent frames (see set_refresh_rate()).
     pub fn take_input(&mut self) -> egui::RawInput {
         self.input.max_texture_side = Some(self.max_texture_side);
+        let time = *self.input.time.get_or_insert_with(|| self.clock.now());
+        self.clock.frame(time);
+        self.input.predicted_dt = self.clock.predicted_dt();
+        self.stats.begin_frame(self.input.events.len());
         let pixels_per_point = self.input.pixels_per_point;
         let take = self.input.take();
         self.input.pixels_per_point = pixels_per_point;
         if let Some(ppp) = pixels_per_point {
             self._pixels_per_point = ppp;
         }
+        std::mem::swap(&mut self.pointer_samples, &mut self.pending_pointer_samples);
+        self.pending_pointer_samples.clear();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&take);
+        }
         take
     }
 
+    /// Seconds since the state was created, the time given to egui by take_input().
+    pub fn time(&self) -> f64 {
+        self.clock.now()
+    }
+
+    /// The predicted_dt given to egui by the next take_input().
+    pub fn predicted_dt(&self) -> f32 {
+        self.clock.predicted_dt()
+    }
+
+    /// Set the display's refresh rate (e.g. 144.0) to use for predicted_dt.
+    ///
+    /// FLTK doesn't report it, by default predicted_dt is estimated from the
+    /// intervals between continuously repainted frames.
+    pub fn set_refresh_rate(&mut self, refresh_rate: Option<f32>) {
+        self.clock.refresh_rate = refresh_rate;
+    }
+
+    /// Limit the frames to `max_fps` per second, None for no limit (the default).
+    pub fn set_max_fps(&mut self, max_fps: Option<f32>) {
+        self.pacer.max_fps = max_fps;
+    }
+
+    pub fn max_fps(&self) -> Option<f32> {
+        self.pacer.max_fps
+    }
+
+    /// Check if the next frame can run, according to max_fps.
+    ///
+    /// Call it before take_input() and skip the frame when false: input keeps accumulating,
+    /// and the event loop is woken up in time for the next frame.
+    pub fn frame_due(&mut self) -> bool {
+        self.pacer.frame_due()
+    }
+
+    /// The stats of the last complete frame, None before the second take_input().
+    pub fn frame_stats(&self) -> Option<&FrameStats> {
+        self.stats.history().back()
+    }
+
+    /// The stats of the recent frames, oldest first.
+    pub fn frame_stats_history(&self) -> impl Iterator<Item = &FrameStats> {
+        self.stats.history().iter()
+    }
+
+    /// Time a step of the current frame, from the end of the previous step (or take_input()).
+    ///
+    /// ```no_run
+    /// # use fltk_egui::{egui, egui_glow::Painter, fltk::{prelude::*, window::GlWindow}, EguiState, FramePhase};
+    /// # fn frame(state: &mut EguiState, ctx: &egui::Context, painter: &mut Painter, win: &mut GlWindow) {
+    /// let output = ctx.run(state.take_input(), |ctx| state.show_frame_stats(ctx));
+    /// state.end_phase(FramePhase::Run);
+    /// let meshes = ctx.tessellate(output.shapes);
+    /// state.end_phase(FramePhase::Tessellate);
+    /// state.count_frame_output(&meshes, &output.textures_delta);
+    /// painter.paint_and_update_textures(
+    ///     state.canvas_size,
+    ///     state.pixels_per_point(),
+    ///     &meshes,
+    ///     &output.textures_delta,
+    /// );
+    /// state.end_phase(FramePhase::Paint);
+    /// win.swap_buffers();
+    /// state.end_phase(FramePhase::Swap);
+    /// # }
+    /// ```
+    pub fn end_phase(&mut self, phase: FramePhase) {
+        self.stats.end_phase(phase);
+    }
+
+    /// Count the meshes and texture changes of the current frame.
+    pub fn count_frame_output(
+        &mut self,
+        primitives: &[egui::ClippedPrimitive],
+        textures_delta: &egui::TexturesDelta,
+    ) {
+        let stats = self.stats.current_mut();
+        stats.count_primitives(primitives);
+        stats.count_textures(textures_delta);
+    }
+
+    /// Show the frame stats in an egui window, to call from within `egui::Context::run`.
+    pub fn show_frame_stats(&self, ctx: &egui::Context) {
+        self.stats.show(ctx);
+    }
+
+    /// Start recording every input returned by take_input(), replacing any ongoing recording.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(InputRecorder::new());
+    }
+
+    /// Stop recording, returns the recording to save or replay.
+    pub fn stop_recording(&mut self) -> Option<InputRecorder> {
+        self.recorder.take()
+    }
+
+    /// Check if the input is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Every pointer position (in points) received for the last taken input, in order.
+    ///
+    /// Unaffected by coalesce_pointer_motion, for drawing apps that need every sample.
+    pub fn pointer_samples(&self) -> &[Pos2] {
+        &self.pointer_samples
+    }
+
     pub fn pixels_per_point(&self) -> f32 {
         self._pixels_per_point
     }
@@ -129,16 +437,135 @@ impl EguiState {
         tmp
     }
 
+    /// Remember what egui wants after running a frame, call it right after `egui::Context::run`.
+    ///
+    /// Used to decide which FLTK events egui takes, e.g. `Event::Shortcut` keys
+    /// only go to egui while it wants keyboard input, and to move the keyboard focus
+    /// to the neighbouring FLTK widgets once Tab is pressed past the last egui widget.
+    pub fn update_interest<W: WidgetExt>(&mut self, win: &mut W, ctx: &egui::Context) {
+        if let Some(event) = self.focus.update(win, ctx) {
+            self.input.events.push(event);
+        }
+        self.interest = EguiInterest {
+            wants_keyboard_input: ctx.wants_keyboard_input(),
+            wants_pointer_input: ctx.wants_pointer_input(),
+            pointer_over_area: ctx.is_pointer_over_area(),
+        };
+    }
+
+    /// What egui was interested in at the end of the last frame.
+    ///
+    /// Before the first frame, egui is assumed to want the pointer.
+    pub fn interest(&self) -> EguiInterest {
+        self.interest
+    }
+
+    /// Check if a drag started inside the window is still in progress.
+    ///
+    /// While captured, the pointer keeps being tracked outside of the window
+    /// and egui only sees it leave once every button has been released.
+    pub fn pointer_captured(&self) -> bool {
+        !self.captured_buttons.is_empty()
+    }
+
+    /// Release every captured button, e.g. when the release event got lost.
+    fn release_captured_buttons(&mut self) {
+        for button in std::mem::take(&mut self.captured_buttons) {
+            self.input.events.push(egui::Event::PointerButton {
+                pos: self.pointer_pos,
+                button,
+                pressed: false,
+                modifiers: self.input.modifiers,
+            })
+        }
+    }
+
+    fn update_pointer_pos(&mut self, (x, y): (i32, i32)) {
+        let ppp = self.pixels_per_point();
+        self.pointer_pos = pos2(x as f32 / ppp, y as f32 / ppp);
+    }
+
+    /// Pointer events egui must not see: outside of the window and not part of a captured drag,
+    /// e.g. events forwarded from a parent window that happened over its other widgets.
+    fn ignores_pointer_event<S: EventSource>(&self, source: &S, event: enums::Event) -> bool {
+        match event {
+            enums::Event::Push
+            | enums::Event::Released
+            | enums::Event::Move
+            | enums::Event::Drag
+            | enums::Event::MouseWheel => !self.pointer_captured() && !source.pointer_in_window(),
+            _ => false,
+        }
+    }
+
+    /// Replace what happens when egui asks to open a url (e.g. a clicked hyperlink).
+    ///
+    /// By default the url is opened with the desktop opener, see [`open_url_with_system`].
+    /// A custom handler can log, veto or redirect navigations.
+    pub fn set_url_handler(&mut self, handler: impl FnMut(&egui::output::OpenUrl) + 'static) {
+        self.url_handler = Box::new(handler);
+    }
+
+    /// Check if egui is interested in an FLTK event, based on the last update_interest().
+    ///
+    /// Pointer events are wanted over egui areas or while egui uses the pointer,
+    /// key events while egui wants keyboard input.
+    pub fn wants_event(&self, event: enums::Event) -> bool {
+        self.wants(event, app::event_key(), app::event_state())
+    }
+
+    fn wants(&self, event: enums::Event, key: enums::Key, keymod: enums::EventState) -> bool {
+        let wants_pointer = self.interest.wants_pointer_input
+            || self.interest.pointer_over_area
+            || self.pointer_captured();
+        match event {
+            enums::Event::Push | enums::Event::Move | enums::Event::MouseWheel => wants_pointer,
+            enums::Event::Drag | enums::Event::Released => self.pointer_captured(),
+            enums::Event::KeyDown | enums::Event::KeyUp | enums::Event::Shortcut => {
+                self.interest.wants_keyboard_input || self.is_zoom_shortcut(key, keymod)
+            }
+            // Taking the focus is what makes FLTK deliver key events to the window.
+            enums::Event::Resize | enums::Event::Focus => true,
+            _ => false,
+        }
+    }
+
     /// Conveniece method bundling the necessary components for input/event handling
-    pub fn fuse_input(&mut self, win: &mut GlWindow, event: enums::Event) {
-        input_to_egui(win, event, self);
+    ///
+    /// Returns whether egui wants the event, to be returned from the FLTK handle closure
+    /// so that unused events propagate to parent groups and menu shortcuts.
+    ///
+    /// `win` is the GlWindow, or the widget egui is drawn into (see `SoftwarePainter`).
+    pub fn fuse_input<W: WidgetExt>(&mut self, win: &mut W, event: enums::Event) -> bool {
+        self.fuse_events(&mut FltkEvents::new(win), event)
+    }
+
+    /// Same as fuse_input(), reading the event from `source` instead of FLTK.
+    pub fn fuse_events<S: EventSource>(&mut self, source: &mut S, event: enums::Event) -> bool {
+        let start = Instant::now();
+        let consumed = self.wants(event, source.event_key(), source.event_state())
+            && !self.ignores_pointer_event(source, event);
+        translate_event(source, event, self);
+        if event == enums::Event::Push && !consumed {
+            // FLTK won't send the release of a Push we don't take, end the click right away.
+            self.release_captured_buttons();
+        } else if event == enums::Event::Push && !self.focus.has_keyboard {
+            // Clicking into egui takes the keyboard back from the FLTK widgets.
+            self.focus.has_keyboard = true;
+            source.take_focus_later();
+        }
+        self.stats.add_input(start.elapsed());
+        consumed
     }
 
     /// Convenience method for outputting what egui emits each frame
-    pub fn fuse_output(&mut self, win: &mut GlWindow, egui_output: egui::PlatformOutput) {
+    pub fn fuse_output<W: WidgetExt>(&mut self, win: &mut W, egui_output: egui::PlatformOutput) {
         if !egui_output.copied_text.is_empty() {
             self.clipboard.set(egui_output.copied_text);
         }
+        if let Some(open_url) = &egui_output.open_url {
+            (self.url_handler)(open_url);
+        }
         if win.damage() {
             win.clear_damage();
         }
@@ -146,10 +573,17 @@ impl EguiState {
     }
 
     /// Convenience method for outputting what egui emits each frame (borrow PlatformOutput)
-    pub fn fuse_output_borrow(&mut self, win: &mut GlWindow, egui_output: &egui::PlatformOutput) {
+    pub fn fuse_output_borrow<W: WidgetExt>(
+        &mut self,
+        win: &mut W,
+        egui_output: &egui::PlatformOutput,
+    ) {
         if !egui_output.copied_text.is_empty() {
             app::copy(&egui_output.copied_text);
         }
+        if let Some(open_url) = &egui_output.open_url {
+            (self.url_handler)(open_url);
+        }
         if win.damage() {
             win.clear_damage();
         }
@@ -157,7 +591,15 @@ impl EguiState {
     }
 
     /// Set visual scale, e.g: 0.8, 1.5, 2.0 .etc (default is 1.0)
+    ///
+    /// The zoom (see set_zoom()) is applied on top of it.
     pub fn set_visual_scale(&mut self, size: f32) {
+        self.visual_scale = size;
+        self.apply_scale();
+    }
+
+    fn apply_scale(&mut self) {
+        let size = self.visual_scale * self.zoom;
         // have to be setted the pixels_per_point of both the inner (input) and the state.
         self.input.pixels_per_point = Some(size);
         self._pixels_per_point = size;
@@ -167,31 +609,82 @@ impl EguiState {
         let rect = vec2(canvas_size[0] as f32, canvas_size[1] as f32) / size;
         self.input.screen_rect = Some(Rect::from_min_size(Default::default(), rect));
     }
+
+    /// The current zoom of the ui (1.0 = no zoom), e.g. to persist it in the app's settings.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Set the zoom of the ui, clamped to zoom_settings.min and zoom_settings.max.
+    ///
+    /// A change makes window_resized() return true, so that the next frame gets painted.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        let zoom = zoom.clamp(self.zoom_settings.min, self.zoom_settings.max);
+        if zoom != self.zoom {
+            self.zoom = zoom;
+            self.apply_scale();
+            self._window_resized = true;
+        }
+    }
+
+    fn is_zoom_shortcut(&self, key: enums::Key, keymod: enums::EventState) -> bool {
+        self.zoom_settings.keyboard_shortcuts
+            && keymod & enums::EventState::Command == enums::EventState::Command
+            && zoom_shortcut(key).is_some()
+    }
+
+    /// Zoom in by zoom_settings.step
+    pub fn zoom_in(&mut self) {
+        self.set_zoom(self.zoom * self.zoom_settings.step);
+    }
+
+    /// Zoom out by zoom_settings.step
+    pub fn zoom_out(&mut self) {
+        self.set_zoom(self.zoom / self.zoom_settings.step);
+    }
+
+    /// Back to no zoom.
+    pub fn reset_zoom(&mut self) {
+        self.set_zoom(1.0);
+    }
 }
 
 /// Handles input/events from FLTK
-pub fn input_to_egui(
-    win: &mut GlWindow,
+pub fn input_to_egui<W: WidgetExt>(
+    win: &mut W,
     event: enums::Event,
     state: &mut EguiState,
     // painter: &mut Painter,
 ) {
+    translate_event(&mut FltkEvents::new(win), event, state)
+}
+
+/// Handles an event read from `source`, see `FltkEvents` and `ScriptedEvents`.
+pub fn translate_event<S: EventSource>(source: &mut S, event: enums::Event, state: &mut EguiState) {
+    if state.ignores_pointer_event(source, event) {
+        // Forwarded from a parent window, the pointer is over one of its other widgets.
+        if state.pointer_in_window {
+            state.pointer_in_window = false;
+            state.input.events.push(egui::Event::PointerGone);
+        }
+        return;
+    }
     match event {
         enums::Event::Resize => {
-            state.canvas_size = [win.width() as u32, win.height() as u32];
-            state.set_visual_scale(state.pixels_per_point());
+            let (width, height) = source.window_size();
+            state.canvas_size = [width as u32, height as u32];
+            state.apply_scale();
             state._window_resized = true;
         }
 
-        //MouseButonLeft pressed is the only one needed by egui
         enums::Event::Push => {
-            let mouse_btn = match app::event_mouse_button() {
-                app::MouseButton::Left => Some(egui::PointerButton::Primary),
-                app::MouseButton::Middle => Some(egui::PointerButton::Middle),
-                app::MouseButton::Right => Some(egui::PointerButton::Secondary),
-                _ => None,
-            };
-            if let Some(pressed) = mouse_btn {
+            state.update_pointer_pos(source.event_coords());
+            if let Some(pressed) = translate_mouse_button(source.event_button()) {
+                // FLTK keeps sending Drag/Released to the window that got the Push,
+                // even once the pointer has left it.
+                if !state.captured_buttons.contains(&pressed) {
+                    state.captured_buttons.push(pressed);
+                }
                 state.input.events.push(egui::Event::PointerButton {
                     pos: state.pointer_pos,
                     button: pressed,
@@ -201,38 +694,75 @@ pub fn input_to_egui(
             }
         }
 
-        //MouseButonLeft pressed is the only one needed by egui
         enums::Event::Released => {
-            // fix unreachable, we can use Option.
-            let mouse_btn = match app::event_mouse_button() {
-                app::MouseButton::Left => Some(egui::PointerButton::Primary),
-                app::MouseButton::Middle => Some(egui::PointerButton::Middle),
-                app::MouseButton::Right => Some(egui::PointerButton::Secondary),
-                _ => None,
-            };
-            if let Some(released) = mouse_btn {
-                state.input.events.push(egui::Event::PointerButton {
-                    pos: state.pointer_pos,
-                    button: released,
-                    pressed: false,
-                    modifiers: state.input.modifiers,
-                })
+            state.update_pointer_pos(source.event_coords());
+            if let Some(released) = translate_mouse_button(source.event_button()) {
+                // Otherwise egui already got a release for it, e.g. the Push wasn't taken.
+                if state.captured_buttons.contains(&released) {
+                    state.captured_buttons.retain(|button| *button != released);
+                    state.input.events.push(egui::Event::PointerButton {
+                        pos: state.pointer_pos,
+                        button: released,
+                        pressed: false,
+                        modifiers: state.input.modifiers,
+                    })
+                }
+            }
+            // The Leave was deferred while the drag was captured.
+            if !state.pointer_captured() && !source.pointer_in_window() {
+                state.pointer_in_window = false;
+                state.input.events.push(egui::Event::PointerGone);
             }
         }
 
         enums::Event::Move | enums::Event::Drag => {
-            let ppp = state.pixels_per_point();
-            let (x, y) = app::event_coords();
-            state.pointer_pos = pos2(x as f32 / ppp, y as f32 / ppp);
-            state
-                .input
-                .events
-                .push(egui::Event::PointerMoved(state.pointer_pos))
+            // A plain Move while buttons are captured means their release got lost.
+            if event == enums::Event::Move && state.pointer_captured() {
+                state.release_captured_buttons();
+            }
+            state.update_pointer_pos(source.event_coords());
+            state.pointer_in_window = true;
+            state.pending_pointer_samples.push(state.pointer_pos);
+            let pos = state.pointer_pos;
+            match state.input.events.last_mut() {
+                // Only merge with the directly preceding motion, to keep ordering with button events.
+                Some(egui::Event::PointerMoved(last)) if state.coalesce_pointer_motion => {
+                    *last = pos
+                }
+                _ => state.input.events.push(egui::Event::PointerMoved(pos)),
+            }
+        }
+
+        enums::Event::Leave => {
+            if !state.pointer_captured() {
+                state.pointer_in_window = false;
+                state.input.events.push(egui::Event::PointerGone);
+            }
+        }
+
+        enums::Event::Focus => {
+            if source.event_key() == enums::Key::Tab {
+                // FLTK tabbed into the window, focus the first (or last) egui widget.
+                if let Some(event) = state
+                    .focus
+                    .enter(source.has_modifier(enums::EventState::Shift))
+                {
+                    state.input.events.push(event);
+                }
+            } else {
+                state.focus.has_keyboard = true;
+            }
+        }
+
+        enums::Event::Unfocus | enums::Event::Hide => {
+            // Drags can't survive losing the window, always end them with a release.
+            state.release_captured_buttons();
+            state.focus.has_keyboard = false;
         }
 
         enums::Event::KeyUp => {
-            if let Some(key) = translate_virtual_key_code(app::event_key()) {
-                let keymod = app::event_state();
+            if let Some(key) = translate_virtual_key_code(source.event_key()) {
+                let keymod = source.event_state();
                 state.input.modifiers = Modifiers {
                     alt: (keymod & enums::EventState::Alt == enums::EventState::Alt),
                     ctrl: (keymod & enums::EventState::Ctrl == enums::EventState::Ctrl),
@@ -250,54 +780,25 @@ pub fn input_to_egui(
             }
         }
 
-        enums::Event::KeyDown => {
-            if let Some(c) = app::event_text().chars().next() {
-                if let Some(del) = app::compose() {
-                    state.input.events.push(Event::Text(c.to_string()));
-                    if del != 0 {
-                        app::compose_reset();
-                    }
-                }
-            }
-            if let Some(key) = translate_virtual_key_code(app::event_key()) {
-                let keymod = app::event_state();
-                state.input.modifiers = Modifiers {
-                    alt: (keymod & enums::EventState::Alt == enums::EventState::Alt),
-                    ctrl: (keymod & enums::EventState::Ctrl == enums::EventState::Ctrl),
-                    shift: (keymod & enums::EventState::Shift == enums::EventState::Shift),
-                    mac_cmd: keymod & enums::EventState::Meta == enums::EventState::Meta,
-
-                    //TOD: Test on both windows and mac
-                    command: (keymod & enums::EventState::Command == enums::EventState::Command),
-                };
-
-                state.input.events.push(Event::Key {
-                    key,
-                    pressed: true,
-                    modifiers: state.input.modifiers,
-                });
-
-                if state.input.modifiers.command && key == Key::C {
-                    // println!("copy event");
-                    state.input.events.push(Event::Copy)
-                } else if state.input.modifiers.command && key == Key::X {
-                    // println!("cut event");
-                    state.input.events.push(Event::Cut)
-                } else {
-                    state.input.events.push(Event::Key {
-                        key,
-                        pressed: false,
-                        modifiers: state.input.modifiers,
-                    })
-                }
+        // Keys no FLTK widget consumed, only taken when egui is listening to the keyboard
+        // (or zooming, as reported by wants_event).
+        enums::Event::Shortcut => {
+            if state.interest.wants_keyboard_input
+                || state.is_zoom_shortcut(source.event_key(), source.event_state())
+            {
+                key_down_to_egui(source, state);
             }
         }
 
+        enums::Event::KeyDown => key_down_to_egui(source, state),
+
         enums::Event::MouseWheel => {
-            if app::is_event_ctrl() {
+            if source.has_modifier(enums::EventState::Ctrl) {
                 let zoom_factor = state.zoom_factor;
-                match app::event_dy() {
-                    app::MouseWheel::Up => {
+                match (state.zoom_settings.wheel, source.event_dy()) {
+                    (ZoomGesture::Ui, app::MouseWheel::Up) => state.zoom_in(),
+                    (ZoomGesture::Ui, app::MouseWheel::Down) => state.zoom_out(),
+                    (ZoomGesture::Event, app::MouseWheel::Up) => {
                         let delta = egui::vec2(1., -1.) * zoom_factor;
 
                         // Treat as zoom in:
@@ -306,7 +807,7 @@ pub fn input_to_egui(
                             .events
                             .push(Event::Zoom((delta.y / 200.0).exp()));
                     }
-                    app::MouseWheel::Down => {
+                    (ZoomGesture::Event, app::MouseWheel::Down) => {
                         let delta = egui::vec2(-1., 1.) * zoom_factor;
 
                         // Treat as zoom out:
@@ -319,7 +820,7 @@ pub fn input_to_egui(
                 }
             } else {
                 let scroll_factor = state.scroll_factor;
-                match app::event_dy() {
+                match source.event_dy() {
                     app::MouseWheel::Up => {
                         state.input.events.push(Event::Scroll(Vec2 {
                             x: 0.,
@@ -343,6 +844,85 @@ pub fn input_to_egui(
     }
 }
 
+fn key_down_to_egui<S: EventSource>(source: &mut S, state: &mut EguiState) {
+    let key = source.event_key();
+    let keymod = source.event_state();
+    if key == enums::Key::Tab
+        && !source.has_modifier(enums::EventState::Ctrl)
+        && !source.has_modifier(enums::EventState::Alt)
+        && !state
+            .focus
+            .tab(source.has_modifier(enums::EventState::Shift), |backward| {
+                source.focus_neighbour(backward)
+            })
+    {
+        // Tab went past the first/last egui widget, the focus moved to an FLTK widget.
+        return;
+    }
+    if state.is_zoom_shortcut(key, keymod) {
+        match zoom_shortcut(key) {
+            Some(ZoomShortcut::In) => state.zoom_in(),
+            Some(ZoomShortcut::Out) => state.zoom_out(),
+            _ => state.reset_zoom(),
+        }
+        return;
+    }
+    if let Some(c) = source.event_text().chars().next() {
+        if let Some(del) = source.compose() {
+            state.input.events.push(Event::Text(c.to_string()));
+            if del != 0 {
+                source.compose_reset();
+            }
+        }
+    }
+    if let Some(key) = translate_virtual_key_code(key) {
+        state.input.modifiers = Modifiers {
+            alt: (keymod & enums::EventState::Alt == enums::EventState::Alt),
+            ctrl: (keymod & enums::EventState::Ctrl == enums::EventState::Ctrl),
+            shift: (keymod & enums::EventState::Shift == enums::EventState::Shift),
+            mac_cmd: keymod & enums::EventState::Meta == enums::EventState::Meta,
+
+            //TOD: Test on both windows and mac
+            command: (keymod & enums::EventState::Command == enums::EventState::Command),
+        };
+
+        state.input.events.push(Event::Key {
+            key,
+            pressed: true,
+            modifiers: state.input.modifiers,
+        });
+
+        if state.input.modifiers.command && key == Key::C {
+            // println!("copy event");
+            state.input.events.push(Event::Copy)
+        } else if state.input.modifiers.command && key == Key::X {
+            // println!("cut event");
+            state.input.events.push(Event::Cut)
+        } else {
+            state.input.events.push(Event::Key {
+                key,
+                pressed: false,
+                modifiers: state.input.modifiers,
+            })
+        }
+    }
+}
+
+/// Translates FLTK mouse buttons (`app::event_button()`) to egui pointer buttons.
+///
+/// FLTK reports the side buttons of a mouse as buttons 4 (back) and 5 (forward),
+/// they are mapped to `Extra1` and `Extra2`, like browsers do.
+pub fn translate_mouse_button(button: i32) -> Option<egui::PointerButton> {
+    match button {
+        1 => Some(egui::PointerButton::Primary),
+        2 => Some(egui::PointerButton::Middle),
+        3 => Some(egui::PointerButton::Secondary),
+        4 => Some(egui::PointerButton::Extra1),
+        5 => Some(egui::PointerButton::Extra2),
+        _ => None,
+    }
+}
+
 /// Translates key codes
 pub fn translate_virtual_key_code(key: enums::Key) -> Option<egui::Key> {
     match key {
@@ -409,39 +989,6 @@ pub fn translate_virtual_key_code(key: enums::Key) -> Option<egui::Key> {
     }
 }
 
-/// Translates FLTK cursor to Egui cursors
-pub fn translate_cursor(
-    win: &mut GlWindow,
-    fused: &mut FusedCursor,
-    cursor_icon: egui::CursorIcon,
-) {
-    let tmp_icon = match cursor_icon {
-        CursorIcon::None => enums::Cursor::None,
-        CursorIcon::Default => enums::Cursor::Arrow,
-        CursorIcon::Help => enums::Cursor::Help,
-        CursorIcon::PointingHand => enums::Cursor::Hand,
-        CursorIcon::ResizeHorizontal => enums::Cursor::WE,
-        CursorIcon::ResizeNeSw => enums::Cursor::NESW,
-        CursorIcon::ResizeNwSe => enums::Cursor::NWSE,
-        CursorIcon::ResizeVertical => enums::Cursor::NS,
-        CursorIcon::Text => enums::Cursor::Insert,
-        CursorIcon::Crosshair => enums::Cursor::Cross,
-        CursorIcon::NotAllowed | CursorIcon::NoDrop => enums::Cursor::Wait,
-        CursorIcon::Wait => enums::Cursor::Wait,
-        CursorIcon::Progress => enums::Cursor::Wait,
-        CursorIcon::Grab => enums::Cursor::Hand,
-        CursorIcon::Grabbing => enums::Cursor::Move,
-        CursorIcon::Move => enums::Cursor::Move,
-
-        _ => enums::Cursor::Arrow,
-    };
-
-    if tmp_icon != fused.cursor_icon {
-        fused.cursor_icon = tmp_icon;
-        win.set_cursor(tmp_icon)
-    }
-}
-
 pub trait EguiImageConvertible<I>
 where
     I: ImageExt,
@@ -518,6 +1065,9 @@ pub trait ColorImageExt {
     fn from_vec_color32(size: [usize; 2], vec: Vec<egui::Color32>) -> Self;
 
     fn from_color32_slice(size: [usize; 2], slice: &[egui::Color32]) -> Self;
+
+    /// Convert premultiplied pixels (e.g. rendered by OffscreenRenderer) to an FLTK image.
+    fn to_rgb_image(&self) -> Result<fltk::image::RgbImage, FltkError>;
 }
 
 impl ColorImageExt for egui::ColorImage {
@@ -542,6 +1092,20 @@ impl ColorImageExt for egui::ColorImage {
         });
         egui::ColorImage::from_rgba_unmultiplied(size, &pixels)
     }
+
+    fn to_rgb_image(&self) -> Result<fltk::image::RgbImage, FltkError> {
+        let pixels: Vec<u8> = self
+            .pixels
+            .iter()
+            .flat_map(|x| x.to_srgba_unmultiplied())
+            .collect();
+        fltk::image::RgbImage::new(
+            &pixels,
+            self.size[0] as i32,
+            self.size[1] as i32,
+            enums::ColorDepth::Rgba8,
+        )
+    }
 }
 
 /// egui::TextureHandle Extender.
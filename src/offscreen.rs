@@ -0,0 +1,292 @@
+use crate::capture::read_pixels;
+#[cfg(feature = "software")]
+use crate::SoftwarePainter;
+use egui::{Color32, ColorImage, FullOutput, Pos2, RawInput, Rect, TexturesDelta};
+use egui_glow::{
+    glow::{self, HasContext},
+    Painter,
+};
+use fltk::{
+    enums::Mode,
+    prelude::{GroupExt, WidgetBase, WidgetExt, WindowExt},
+    window::GlWindow,
+};
+use std::sync::Arc;
+
+enum Target {
+    Glow {
+        painter: Painter,
+        /// Owns the GL context, None when borrowing the context of the app.
+        window: Option<GlWindow>,
+    },
+    #[cfg(feature = "software")]
+    Software(SoftwarePainter),
+}
+
+/// Renders egui frames to images without a visible window, e.g. for documentation
+/// screenshots and golden-image tests on CI machines (Mesa llvmpipe, Xvfb).
+///
+/// ```no_run
+/// use fltk_egui::{egui, OffscreenRenderer};
+///
+/// let mut renderer = OffscreenRenderer::new().unwrap();
+/// let image = renderer.render([400, 300], 2.0, |ctx| {
+///     egui::CentralPanel::default().show(ctx, |ui| ui.heading("Hello"));
+/// });
+/// assert_eq!(image.size, [400, 300]);
+/// ```
+pub struct OffscreenRenderer {
+    ctx: egui::Context,
+    target: Target,
+    /// Frames run per render, the last one is painted. egui needs a couple of frames
+    /// to settle sizes (e.g. auto-sized windows), default value is 2.
+    pub frames: usize,
+    /// Color of the image before egui is painted, default value is transparent black.
+    pub clear_color: Color32,
+    time: f64,
+}
+
+impl OffscreenRenderer {
+    /// Render with OpenGL, on the context of a hidden window.
+    ///
+    /// FLTK only creates GL contexts for shown windows, so a 1x1 borderless window
+    /// is shown off-screen, outside of the window manager's control; the frames are
+    /// rendered into a framebuffer object of the requested size. An OpenGL 3 context
+    /// is tried first, then the driver's default one.
+    ///
+    /// Wayland clients can't position their windows: there the 1x1 window is placed by
+    /// the compositor and may show on screen. Use `with_gl` (or `software`) instead when
+    /// that matters.
+    pub fn new() -> Result<Self, String> {
+        let mut errors = Vec::new();
+        for mode in [Mode::Opengl3 | Mode::Alpha, Mode::Alpha] {
+            let mut window = GlWindow::new(-100, -100, 1, 1, None);
+            window.set_mode(mode);
+            window.set_border(false);
+            window.set_override();
+            window.end();
+            match hidden_window_painter(&mut window) {
+                Ok(painter) => {
+                    return Ok(Self::with_target(Target::Glow {
+                        painter,
+                        window: Some(window),
+                    }))
+                }
+                Err(error) => {
+                    window.hide();
+                    GlWindow::delete(window);
+                    errors.push(error);
+                }
+            }
+        }
+        Err(errors.join(", then "))
+    }
+
+    /// Render with OpenGL, on an existing context (e.g. `painter.gl()` of the app).
+    ///
+    /// The context has to be current when rendering.
+    pub fn with_gl(gl: Arc<glow::Context>) -> Result<Self, String> {
+        let painter = Painter::new(gl, None, "")?;
+        Ok(Self::with_target(Target::Glow {
+            painter,
+            window: None,
+        }))
+    }
+
+    /// Render on the CPU, no display or OpenGL needed.
+    #[cfg(feature = "software")]
+    pub fn software() -> Self {
+        Self::with_target(Target::Software(SoftwarePainter::new()))
+    }
+
+    fn with_target(target: Target) -> Self {
+        Self {
+            ctx: egui::Context::default(),
+            target,
+            frames: 2,
+            clear_color: Color32::TRANSPARENT,
+            time: 0.0,
+        }
+    }
+
+    /// The egui context the frames are run with, e.g. to set fonts or visuals.
+    pub fn context(&self) -> &egui::Context {
+        &self.ctx
+    }
+
+    /// Start over with a new egui context and the clock at 0, so the next render doesn't
+    /// depend on the previous ones (memory of windows, animations...).
+    ///
+    /// The fonts, style and other options set on `context()` are kept.
+    pub fn reset(&mut self) {
+        // set_fonts() only takes effect on the next frame.
+        let _ = self.ctx.run(RawInput::default(), |_| {});
+        let fonts = self.ctx.fonts().lock().fonts.definitions().clone();
+        let options = self.ctx.options().clone();
+        self.ctx = egui::Context::default();
+        self.ctx.set_fonts(fonts);
+        *self.ctx.options() = options;
+        self.time = 0.0;
+    }
+
+    fn max_texture_side(&self) -> usize {
+        match &self.target {
+            Target::Glow { painter, .. } => painter.max_texture_side(),
+            #[cfg(feature = "software")]
+            Target::Software(painter) => painter.max_texture_side(),
+        }
+    }
+
+    /// Run `ui` for `frames` frames at `size` (in pixels) and `pixels_per_point`,
+    /// returns the painted image, with premultiplied alpha.
+    pub fn render(
+        &mut self,
+        size: [u32; 2],
+        pixels_per_point: f32,
+        mut ui: impl FnMut(&egui::Context),
+    ) -> ColorImage {
+        let screen_rect = Rect::from_min_size(
+            Pos2::ZERO,
+            egui::vec2(size[0] as f32, size[1] as f32) / pixels_per_point,
+        );
+        let mut output = FullOutput::default();
+        for frame in 0..self.frames.max(1) {
+            let input = RawInput {
+                screen_rect: Some(screen_rect),
+                pixels_per_point: Some(pixels_per_point),
+                max_texture_side: Some(self.max_texture_side()),
+                time: Some(self.time),
+                ..Default::default()
+            };
+            self.time += input.predicted_dt as f64;
+            if frame > 0 {
+                // The textures of the frames that aren't painted are still needed.
+                self.update_textures(&output.textures_delta);
+            }
+            output = self.ctx.run(input, &mut ui);
+        }
+
+        let primitives = self.ctx.tessellate(output.shapes);
+        let clear_color = self.clear_color;
+        match &mut self.target {
+            Target::Glow { painter, window } => {
+                if let Some(window) = window {
+                    window.make_current();
+                }
+                unsafe {
+                    render_to_fbo(painter, size, clear_color, |painter| {
+                        painter.paint_and_update_textures(
+                            size,
+                            pixels_per_point,
+                            &primitives,
+                            &output.textures_delta,
+                        )
+                    })
+                }
+            }
+            #[cfg(feature = "software")]
+            Target::Software(painter) => {
+                painter.clear_color = clear_color;
+                painter.paint_and_update_textures(
+                    size,
+                    pixels_per_point,
+                    &primitives,
+                    &output.textures_delta,
+                );
+                painter.capture()
+            }
+        }
+    }
+
+    fn update_textures(&mut self, textures_delta: &TexturesDelta) {
+        match &mut self.target {
+            Target::Glow { painter, window } => {
+                if let Some(window) = window {
+                    window.make_current();
+                }
+                for (id, image_delta) in &textures_delta.set {
+                    painter.set_texture(*id, image_delta);
+                }
+                for &id in &textures_delta.free {
+                    painter.free_texture(id);
+                }
+            }
+            #[cfg(feature = "software")]
+            Target::Software(painter) => {
+                for (id, image_delta) in &textures_delta.set {
+                    painter.set_texture(*id, image_delta);
+                }
+                for &id in &textures_delta.free {
+                    painter.free_texture(id);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for OffscreenRenderer {
+    fn drop(&mut self) {
+        if let Target::Glow { painter, window } = &mut self.target {
+            if let Some(window) = window.as_mut() {
+                window.make_current();
+            }
+            painter.destroy();
+            if let Some(window) = window.take() {
+                GlWindow::delete(window);
+            }
+        }
+    }
+}
+
+/// Shows `window` off-screen and creates a painter on its context.
+fn hidden_window_painter(window: &mut GlWindow) -> Result<Painter, String> {
+    window.show();
+    window.make_current();
+    if window.context().is_none() {
+        return Err("no OpenGL context".to_string());
+    }
+    let gl = unsafe { glow::Context::from_loader_function(|s| window.get_proc_address(s) as _) };
+    crate::gl_callback::load_bind_fns(&gl, |s| window.get_proc_address(s) as _);
+    Painter::new(Arc::from(gl), None, "")
+}
+
+/// Paints into a framebuffer object of `size` and reads it back.
+unsafe fn render_to_fbo(
+    painter: &mut Painter,
+    [width, height]: [u32; 2],
+    clear_color: Color32,
+    paint: impl FnOnce(&mut Painter),
+) -> ColorImage {
+    let gl = painter.gl().clone();
+    let fbo = gl.create_framebuffer().ok();
+    let color = gl.create_renderbuffer().ok();
+    gl.bind_framebuffer(glow::FRAMEBUFFER, fbo);
+    gl.bind_renderbuffer(glow::RENDERBUFFER, color);
+    gl.renderbuffer_storage(glow::RENDERBUFFER, glow::RGBA8, width as i32, height as i32);
+    gl.framebuffer_renderbuffer(
+        glow::FRAMEBUFFER,
+        glow::COLOR_ATTACHMENT0,
+        glow::RENDERBUFFER,
+        color,
+    );
+
+    gl.disable(glow::SCISSOR_TEST);
+    gl.viewport(0, 0, width as i32, height as i32);
+    let [r, g, b, a] = clear_color.to_array().map(|c| c as f32 / 255.0);
+    gl.clear_color(r, g, b, a);
+    gl.clear(glow::COLOR_BUFFER_BIT);
+    paint(painter);
+
+    let image = read_pixels(&gl, [width, height]);
+
+    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+    gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+    if let Some(fbo) = fbo {
+        gl.delete_framebuffer(fbo);
+    }
+    if let Some(color) = color {
+        gl.delete_renderbuffer(color);
+    }
+
+    image
+}
@@ -0,0 +1,34 @@
+use egui::output::OpenUrl;
+use std::{io, process::Command};
+
+/// Called with every URL egui asks to open (see `EguiState::set_url_handler`).
+pub type UrlHandler = Box<dyn FnMut(&OpenUrl)>;
+
+/// The default url handler: hands the url to the desktop opener
+/// (`xdg-open` on Linux/BSD, `open` on macOS, `explorer` on Windows).
+///
+/// Desktop openers always go through the user's default browser, which decides
+/// itself whether a new tab is used, so `new_tab` is only meaningful to custom handlers.
+pub fn open_url_with_system(open_url: &OpenUrl) -> io::Result<()> {
+    let opener = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    // Don't block the event loop on the opener, some of them only return once the
+    // browser exits; reap it from a thread instead so no zombie process is left behind.
+    let mut child = Command::new(opener).arg(&open_url.url).spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+pub(crate) fn default_url_handler() -> UrlHandler {
+    Box::new(|open_url: &OpenUrl| {
+        if let Err(err) = open_url_with_system(open_url) {
+            eprintln!("Failed to open url {}: {}", open_url.url, err);
+        }
+    })
+}
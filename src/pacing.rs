@@ -0,0 +1,144 @@
+use fltk::{app, window::GlWindow};
+use std::{
+    cell::Cell,
+    os::raw::c_int,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// Limits how often egui frames are run, see `EguiState::frame_due`.
+///
+/// Nothing is enforced when painting: the event loop skips the frames that aren't due.
+#[derive(Default)]
+pub(crate) struct FramePacer {
+    pub max_fps: Option<f32>,
+    last_frame: Option<Instant>,
+    /// A timeout is set to wake up the event loop for the next frame.
+    wake_pending: Rc<Cell<bool>>,
+}
+
+impl FramePacer {
+    pub fn frame_due(&mut self) -> bool {
+        let interval = match self.max_fps.filter(|fps| *fps > 0.0) {
+            Some(fps) => Duration::from_secs_f32(1.0 / fps),
+            None => return true,
+        };
+        let now = Instant::now();
+        let due = self.last_frame.map_or(now, |last| last + interval);
+        if now >= due {
+            self.last_frame = Some(now);
+            return true;
+        }
+        // Input keeps accumulating until then, make sure it gets a frame.
+        if !self.wake_pending.replace(true) {
+            let wake_pending = self.wake_pending.clone();
+            app::add_timeout3((due - now).as_secs_f64(), move |_| {
+                wake_pending.set(false);
+                app::awake();
+            });
+        }
+        false
+    }
+}
+
+/// Set the swap interval of the GlWindow's context: 0 disables vsync, 1 waits for every
+/// vertical blank. The window must be shown and its context current.
+///
+/// Uses `WGL_EXT_swap_control` on Windows, `GLX_EXT_swap_control`, `GLX_MESA_swap_control`
+/// or `GLX_SGI_swap_control` (which can't disable vsync) on X11 and CGL on macOS.
+/// Returns false when the driver offers no way to set it, e.g. under Wayland.
+pub fn set_swap_interval(win: &GlWindow, interval: i32) -> bool {
+    unsafe { swap_interval(win, interval) }
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn swap_interval(win: &GlWindow, interval: i32) -> bool {
+    let ptr = win.get_proc_address("wglSwapIntervalEXT");
+    if ptr.is_null() {
+        return false;
+    }
+    let wgl_swap_interval: extern "system" fn(c_int) -> c_int = std::mem::transmute(ptr);
+    wgl_swap_interval(interval) != 0
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn swap_interval(_win: &GlWindow, interval: i32) -> bool {
+    use std::os::raw::c_void;
+    const K_CGLCP_SWAP_INTERVAL: c_int = 222;
+    #[link(name = "OpenGL", kind = "framework")]
+    extern "C" {
+        fn CGLGetCurrentContext() -> *mut c_void;
+        fn CGLSetParameter(ctx: *mut c_void, param: c_int, value: *const c_int) -> c_int;
+    }
+    let ctx = CGLGetCurrentContext();
+    !ctx.is_null() && CGLSetParameter(ctx, K_CGLCP_SWAP_INTERVAL, &interval) == 0
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn swap_interval(win: &GlWindow, interval: i32) -> bool {
+    use std::{
+        ffi::CStr,
+        os::raw::{c_char, c_uint, c_ulong, c_void},
+    };
+    const GLX_SCREEN: c_int = 0x800C;
+    // FLTK links libGL for the GlWindow, these are GLX 1.3 entry points.
+    #[link(name = "GL")]
+    extern "C" {
+        fn glXGetCurrentDisplay() -> *mut c_void;
+        fn glXGetCurrentDrawable() -> c_ulong;
+        fn glXGetCurrentContext() -> *mut c_void;
+        fn glXQueryContext(
+            display: *mut c_void,
+            ctx: *mut c_void,
+            attribute: c_int,
+            value: *mut c_int,
+        ) -> c_int;
+        fn glXQueryExtensionsString(display: *mut c_void, screen: c_int) -> *const c_char;
+    }
+
+    // No current GLX context, e.g. FLTK uses EGL under Wayland.
+    let display = glXGetCurrentDisplay();
+    let ctx = glXGetCurrentContext();
+    if display.is_null() || ctx.is_null() {
+        return false;
+    }
+    let mut screen = 0;
+    glXQueryContext(display, ctx, GLX_SCREEN, &mut screen);
+    let extensions = glXQueryExtensionsString(display, screen);
+    if extensions.is_null() {
+        return false;
+    }
+    let extensions = CStr::from_ptr(extensions).to_string_lossy();
+    let has = |name: &str| extensions.split_whitespace().any(|ext| ext == name);
+
+    // glXGetProcAddress returns an address for any name, only use it for listed extensions.
+    let proc_address = |name: &str| -> Option<*const c_void> {
+        let ptr = win.get_proc_address(name);
+        if ptr.is_null() {
+            None
+        } else {
+            Some(ptr as *const c_void)
+        }
+    };
+    if has("GLX_EXT_swap_control") {
+        if let Some(ptr) = proc_address("glXSwapIntervalEXT") {
+            let swap_interval: extern "C" fn(*mut c_void, c_ulong, c_int) =
+                std::mem::transmute(ptr);
+            swap_interval(display, glXGetCurrentDrawable(), interval);
+            return true;
+        }
+    }
+    if has("GLX_MESA_swap_control") {
+        if let Some(ptr) = proc_address("glXSwapIntervalMESA") {
+            let swap_interval: extern "C" fn(c_uint) -> c_int = std::mem::transmute(ptr);
+            return swap_interval(interval as c_uint) == 0;
+        }
+    }
+    if has("GLX_SGI_swap_control") && interval > 0 {
+        if let Some(ptr) = proc_address("glXSwapIntervalSGI") {
+            let swap_interval: extern "C" fn(c_int) -> c_int = std::mem::transmute(ptr);
+            return swap_interval(interval) == 0;
+        }
+    }
+    false
+}
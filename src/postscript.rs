@@ -0,0 +1,246 @@
+use crate::vector::Canvas;
+use egui::{Pos2, Rect};
+use fltk::enums::{Color, Font};
+use std::fmt::Write;
+
+/// Writes what `VectorPainter` draws as a one page PostScript document.
+///
+/// The page is the size of the exported rect, in points (1/72 inch), the y axis is
+/// flipped so that coordinates are the same as with fltk::draw.
+pub(crate) struct PostScript {
+    out: String,
+    /// Font name and size.
+    font: (&'static str, f32),
+}
+
+impl PostScript {
+    pub fn new(width: f32, height: f32) -> Self {
+        let mut out = String::new();
+        let (width, height) = (width.ceil().max(1.0), height.ceil().max(1.0));
+        writeln!(out, "%!PS-Adobe-3.0").ok();
+        writeln!(out, "%%Creator: fltk-egui").ok();
+        writeln!(out, "%%BoundingBox: 0 0 {} {}", width, height).ok();
+        writeln!(out, "%%Pages: 1").ok();
+        writeln!(out, "%%EndComments").ok();
+        // Latin-1 versions of the standard fonts, for accented letters.
+        out.push_str(
+            "/latin1 { findfont dup length dict begin\n\
+             { 1 index /FID ne { def } { pop pop } ifelse } forall\n\
+             /Encoding ISOLatin1Encoding def currentdict end definefont pop } bind def\n",
+        );
+        for font in FONTS {
+            writeln!(out, "/{0}-Latin1 /{0} latin1", font).ok();
+        }
+        writeln!(out, "%%Page: 1 1").ok();
+        writeln!(out, "1 setlinejoin 1 setlinecap").ok();
+        writeln!(out, "0 {} translate 1 -1 scale", height).ok();
+        Self {
+            out,
+            font: ("Helvetica", 12.0),
+        }
+    }
+
+    /// The document, to write to a file.
+    pub fn finish(mut self) -> String {
+        self.out.push_str("showpage\n%%EOF\n");
+        self.out
+    }
+
+    fn path(&mut self, points: &[Pos2]) {
+        self.out.push_str("newpath");
+        for (i, pos) in points.iter().enumerate() {
+            let op = if i == 0 { "moveto" } else { "lineto" };
+            write!(self.out, " {:.2} {:.2} {}", pos.x, pos.y, op).ok();
+        }
+        self.out.push('\n');
+    }
+
+    fn circle(&mut self, center: Pos2, radius: f32) {
+        writeln!(
+            self.out,
+            "newpath {:.2} {:.2} {:.2} 0 360 arc closepath",
+            center.x, center.y, radius
+        )
+        .ok();
+    }
+}
+
+const FONTS: [&str; 4] = [
+    "Helvetica",
+    "Helvetica-Oblique",
+    "Courier",
+    "Courier-Oblique",
+];
+
+impl Canvas for PostScript {
+    fn set_color(&mut self, color: Color) {
+        let (r, g, b) = color.to_rgb();
+        writeln!(
+            self.out,
+            "{:.3} {:.3} {:.3} setrgbcolor",
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0
+        )
+        .ok();
+    }
+
+    fn set_line_width(&mut self, width: f32) {
+        writeln!(self.out, "{:.2} setlinewidth", width).ok();
+    }
+
+    fn stroke(&mut self, points: &[Pos2], closed: bool) {
+        self.path(points);
+        if closed {
+            self.out.push_str("closepath ");
+        }
+        self.out.push_str("stroke\n");
+    }
+
+    fn fill(&mut self, points: &[Pos2]) {
+        self.path(points);
+        self.out.push_str("closepath eofill\n");
+    }
+
+    fn stroke_circle(&mut self, center: Pos2, radius: f32) {
+        self.circle(center, radius);
+        self.out.push_str("stroke\n");
+    }
+
+    fn fill_circle(&mut self, center: Pos2, radius: f32) {
+        self.circle(center, radius);
+        self.out.push_str("fill\n");
+    }
+
+    fn set_font(&mut self, font: Font, size: f32) {
+        let name = if font == Font::Courier {
+            FONTS[2]
+        } else if font == Font::CourierItalic {
+            FONTS[3]
+        } else if font == Font::HelveticaItalic {
+            FONTS[1]
+        } else {
+            FONTS[0]
+        };
+        self.font = (name, size);
+        writeln!(self.out, "/{}-Latin1 {:.2} selectfont", name, size).ok();
+    }
+
+    fn descent(&mut self) -> f32 {
+        // From the fonts' AFM metrics.
+        let (name, size) = self.font;
+        if name.starts_with("Courier") {
+            0.157 * size
+        } else {
+            0.207 * size
+        }
+    }
+
+    fn text(&mut self, text: &str, pos: Pos2, degrees: i32) {
+        // Back to the y axis going up, for the glyphs not to be mirrored.
+        write!(
+            self.out,
+            "gsave {:.2} {:.2} translate 1 -1 scale {} rotate 0 0 moveto (",
+            pos.x, pos.y, degrees
+        )
+        .ok();
+        for c in text.chars() {
+            match c {
+                '(' | ')' | '\\' => {
+                    self.out.push('\\');
+                    self.out.push(c);
+                }
+                ' '..='~' => self.out.push(c),
+                // Latin-1, anything else isn't in the fonts.
+                c => {
+                    let code = if (c as u32) < 256 {
+                        c as u32
+                    } else {
+                        '?' as u32
+                    };
+                    write!(self.out, "\\{:03o}", code).ok();
+                }
+            }
+        }
+        self.out.push_str(") show grestore\n");
+    }
+
+    fn image(&mut self, rgb: &[u8], [width, height]: [usize; 2], rect: Rect) {
+        writeln!(
+            self.out,
+            "gsave {:.2} {:.2} translate {:.2} {:.2} scale",
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height()
+        )
+        .ok();
+        // Rows top to bottom, as the y axis goes down.
+        writeln!(
+            self.out,
+            "{0} {1} 8 [{0} 0 0 {1} 0 0] currentfile /ASCIIHexDecode filter false 3 colorimage",
+            width, height
+        )
+        .ok();
+        for row in rgb.chunks(32) {
+            for byte in row {
+                write!(self.out, "{:02x}", byte).ok();
+            }
+            self.out.push('\n');
+        }
+        self.out.push_str(">\ngrestore\n");
+    }
+
+    fn push_clip(&mut self, rect: Rect) {
+        writeln!(
+            self.out,
+            "gsave {:.2} {:.2} {:.2} {:.2} rectclip",
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height()
+        )
+        .ok();
+    }
+
+    fn pop_clip(&mut self) {
+        self.out.push_str("grestore\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document() {
+        let mut ps = PostScript::new(100.5, 40.0);
+        ps.set_color(Color::from_rgb(255, 0, 0));
+        ps.fill(&[
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 5.0),
+        ]);
+        ps.set_font(Font::Courier, 12.0);
+        ps.text("(a\\b) é€", Pos2::new(1.0, 2.0), 90);
+        ps.image(
+            &[255, 0, 128],
+            [1, 1],
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(4.0, 4.0)),
+        );
+        let ps = ps.finish();
+
+        assert!(ps.starts_with("%!PS-Adobe-3.0\n"));
+        assert!(ps.contains("%%BoundingBox: 0 0 101 40\n"));
+        assert!(ps.contains("0 40 translate 1 -1 scale\n"));
+        assert!(ps.contains("1.000 0.000 0.000 setrgbcolor\n"));
+        assert!(ps.contains(
+            "newpath 0.00 0.00 moveto 10.00 0.00 lineto 10.00 5.00 lineto\nclosepath eofill\n"
+        ));
+        assert!(ps.contains("/Courier-Latin1 12.00 selectfont\n"));
+        assert!(ps.contains("90 rotate 0 0 moveto (\\(a\\\\b\\) \\351?) show grestore\n"));
+        assert!(ps.contains("colorimage\nff0080\n>\n"));
+        assert!(ps.ends_with("showpage\n%%EOF\n"));
+        assert_eq!(PostScript::new(1.0, 1.0).descent(), 0.207 * 12.0);
+    }
+}
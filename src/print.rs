@@ -0,0 +1,166 @@
+use crate::VectorPainter;
+use egui::{epaint::ClippedShape, Rect};
+use fltk::{draw, prelude::FltkError, printer::Printer};
+
+/// How `print` sizes the egui rect on the pages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PrintScale {
+    /// Shrink or grow the rect to fit one page.
+    FitPage,
+    /// Fill the width of the page, continuing on as many pages as needed.
+    FitWidth,
+    /// Printer units (1/72 inch) per egui point, continuing on as many pages as
+    /// needed in both directions. 0.75 prints a 96 dpi screen at its size.
+    Scale(f32),
+}
+
+impl Default for PrintScale {
+    fn default() -> Self {
+        Self::FitWidth
+    }
+}
+
+/// Print `shapes` (the `FullOutput::shapes` of a frame, before tessellation) covering
+/// `rect`, e.g. the screen rect or the rect of a panel, after showing the print dialog.
+///
+/// The shapes are drawn with `painter` (see `VectorPainter`), so text and lines are
+/// rasterized at the printer's resolution. What isn't a vector in egui is approximated:
+/// `PaintCallback`s (custom GL drawing) are skipped, image meshes are printed as RGB
+/// images over `painter.background`, other meshes as flat triangles of their average
+/// vertex color, and line widths are rounded to whole printer units.
+///
+/// Pages are printed top to bottom, then left to right. Returns the number of printed
+/// pages (0 for an empty rect, without showing the dialog), an error when the dialog
+/// is cancelled, the printer fails or the scale isn't positive.
+///
+/// ```no_run
+/// # let ctx = fltk_egui::egui::Context::default();
+/// # let output = ctx.run(Default::default(), |_| {});
+/// let mut painter = fltk_egui::VectorPainter::new();
+/// painter.update_textures(&output.textures_delta);
+/// let rect = ctx.input().screen_rect();
+/// fltk_egui::print(&painter, rect, &output.shapes, fltk_egui::PrintScale::FitWidth).unwrap();
+/// ```
+pub fn print(
+    painter: &VectorPainter,
+    rect: Rect,
+    shapes: &[ClippedShape],
+    scale: PrintScale,
+) -> Result<usize, FltkError> {
+    if !rect.is_positive() {
+        return Ok(0);
+    }
+    let mut printer = Printer::default();
+    // The page count isn't known before the page size.
+    printer.begin_job(0)?;
+
+    let mut pages = 0;
+    let mut layout = None;
+    let result = loop {
+        if let Err(error) = printer.begin_page() {
+            break Err(error);
+        }
+        let (page_w, page_h) = printer.printable_rect();
+        let (factor, rows, total) = match layout {
+            Some(layout) => layout,
+            None => match page_layout(rect, scale, page_w, page_h) {
+                Some(page_layout) => *layout.insert(page_layout),
+                None => {
+                    printer.end_page().ok();
+                    break Err(FltkError::Unknown(format!(
+                        "can't print {:?} on pages of {}x{} with {:?}",
+                        rect, page_w, page_h, scale
+                    )));
+                }
+            },
+        };
+
+        let (column, row) = (pages / rows, pages % rows);
+        let origin = (
+            -(column as f32) * page_w as f32,
+            -(row as f32) * page_h as f32,
+        );
+        draw::push_clip(0, 0, page_w, page_h);
+        painter.paint_at(rect, shapes, factor, origin);
+        draw::pop_clip();
+        pages += 1;
+
+        if let Err(error) = printer.end_page() {
+            break Err(error);
+        }
+        if pages == total {
+            break Ok(pages);
+        }
+    };
+    printer.end_job();
+    result
+}
+
+/// The scale, rows and total number of pages to print `rect` on pages of `page_w` x `page_h`,
+/// None when `rect` can't be printed at a sensible scale.
+fn page_layout(
+    rect: Rect,
+    scale: PrintScale,
+    page_w: i32,
+    page_h: i32,
+) -> Option<(f32, usize, usize)> {
+    let factor = match scale {
+        PrintScale::FitPage => (page_w as f32 / rect.width()).min(page_h as f32 / rect.height()),
+        PrintScale::FitWidth => page_w as f32 / rect.width(),
+        PrintScale::Scale(scale) => scale,
+    };
+    if !factor.is_finite() || factor <= 0.0 {
+        return None;
+    }
+    let count = |length: f32, page: i32| {
+        // Don't start a page for rounding errors.
+        ((length * factor - 0.5) / page.max(1) as f32)
+            .ceil()
+            .max(1.0) as usize
+    };
+    let rows = count(rect.height(), page_h);
+    let total = count(rect.width(), page_w).checked_mul(rows)?;
+    Some((factor, rows, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{pos2, vec2};
+
+    #[test]
+    fn layout() {
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(800.0, 1000.0));
+        assert_eq!(
+            page_layout(rect, PrintScale::FitWidth, 400, 600),
+            Some((0.5, 1, 1))
+        );
+        assert_eq!(
+            page_layout(rect, PrintScale::Scale(1.0), 400, 600),
+            Some((1.0, 2, 4))
+        );
+        assert_eq!(
+            page_layout(rect, PrintScale::FitPage, 400, 300),
+            Some((0.3, 1, 1))
+        );
+    }
+
+    #[test]
+    fn layout_rejects_bad_scales() {
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(800.0, 1000.0));
+        let empty = Rect::from_min_size(pos2(0.0, 0.0), vec2(0.0, 1000.0));
+        assert_eq!(page_layout(empty, PrintScale::FitWidth, 400, 600), None);
+        assert_eq!(page_layout(rect, PrintScale::FitWidth, 0, 600), None);
+        assert_eq!(page_layout(rect, PrintScale::Scale(0.0), 400, 600), None);
+        assert_eq!(page_layout(rect, PrintScale::Scale(-1.0), 400, 600), None);
+        assert_eq!(
+            page_layout(rect, PrintScale::Scale(f32::NAN), 400, 600),
+            None
+        );
+        assert_eq!(
+            page_layout(rect, PrintScale::Scale(f32::INFINITY), 400, 600),
+            None
+        );
+        assert_eq!(page_layout(rect, PrintScale::Scale(1e30), 1, 1), None);
+    }
+}
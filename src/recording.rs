@@ -0,0 +1,687 @@
+use egui::{
+    pos2, Event, HoveredFile, Key, Modifiers, PointerButton, RawInput, Rect, TouchDeviceId,
+    TouchId, TouchPhase,
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+const HEADER: &str = "fltk-egui-recording";
+const VERSION: u32 = 1;
+
+/// One frame's input, with the seconds elapsed since the recording started.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedFrame {
+    pub elapsed: f64,
+    pub input: RawInput,
+}
+
+/// Records the input of each frame, see `EguiState::start_recording`.
+///
+/// Recordings are saved as plain text, one line per field or event, so they can be
+/// attached to tickets, diffed and kept as regression tests. The first line holds the
+/// format version, it only changes when older recordings can't be read anymore.
+/// Dropped/hovered files only keep their path, name and mime type, not their content.
+///
+/// ```text
+/// fltk-egui-recording 1
+/// frame 0.016
+/// screen_rect 0 0 533.3333 400
+/// pixels_per_point 1.5
+/// time 1.25
+/// event pointer_moved 120 48
+/// event text 'hello\sworld
+/// end
+/// ```
+pub struct InputRecorder {
+    start: Instant,
+    frames: Vec<RecordedFrame>,
+}
+
+impl Default for InputRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputRecorder {
+    /// Construct a new recorder, timestamps start now.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Record the input of a frame.
+    pub fn record(&mut self, input: &RawInput) {
+        self.frames.push(RecordedFrame {
+            elapsed: self.start.elapsed().as_secs_f64(),
+            input: input.clone(),
+        });
+    }
+
+    /// The frames recorded so far.
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// Save the recording to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Write the recording to any writer.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{} {}", HEADER, VERSION)?;
+        for frame in &self.frames {
+            write_frame(&mut writer, frame)?;
+        }
+        Ok(())
+    }
+
+    /// Turn the recording into a replayer, without going through a file.
+    pub fn into_replayer(self) -> InputReplayer {
+        InputReplayer {
+            frames: self.frames,
+            next: 0,
+        }
+    }
+}
+
+/// Replays recorded input, frame by frame.
+pub struct InputReplayer {
+    frames: Vec<RecordedFrame>,
+    next: usize,
+}
+
+impl InputReplayer {
+    /// Load a recording saved by `InputRecorder::save`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Read a recording from any reader.
+    pub fn read_from(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines().enumerate();
+        let version = match lines.next() {
+            Some((_, line)) => {
+                let line = line?;
+                let mut tokens = line.split(' ');
+                if tokens.next() != Some(HEADER) {
+                    return Err(invalid(1, "not an fltk-egui recording"));
+                }
+                tokens.next().and_then(|v| v.parse::<u32>().ok())
+            }
+            None => return Err(invalid(1, "empty recording")),
+        };
+        if version != Some(VERSION) {
+            return Err(invalid(1, "unsupported recording version"));
+        }
+
+        let mut frames = Vec::new();
+        let mut frame: Option<RecordedFrame> = None;
+        let mut last_line = 1;
+        for (idx, line) in lines {
+            let line = line?;
+            let line_no = idx + 1;
+            last_line = line_no;
+            let mut tokens = Tokens {
+                iter: line.split(' '),
+                line: line_no,
+            };
+            let field = match tokens.iter.next() {
+                Some("") | None => continue,
+                Some(field) => field,
+            };
+            if field == "frame" {
+                if frame.is_some() {
+                    return Err(invalid(line_no, "frame without end"));
+                }
+                frame = Some(RecordedFrame {
+                    elapsed: tokens.parse()?,
+                    input: RawInput::default(),
+                });
+                continue;
+            }
+            let current = frame
+                .as_mut()
+                .ok_or_else(|| invalid(line_no, "field outside of a frame"))?;
+            let input = &mut current.input;
+            match field {
+                "end" => frames.extend(frame.take()),
+                "screen_rect" => {
+                    let min = pos2(tokens.parse()?, tokens.parse()?);
+                    let max = pos2(tokens.parse()?, tokens.parse()?);
+                    input.screen_rect = Some(Rect::from_min_max(min, max));
+                }
+                "pixels_per_point" => input.pixels_per_point = Some(tokens.parse()?),
+                "max_texture_side" => input.max_texture_side = Some(tokens.parse()?),
+                "time" => input.time = Some(tokens.parse()?),
+                "predicted_dt" => input.predicted_dt = tokens.parse()?,
+                "modifiers" => input.modifiers = tokens.modifiers()?,
+                "has_focus" => input.has_focus = tokens.bool()?,
+                "hovered_file" => input.hovered_files.push(HoveredFile {
+                    path: tokens.path()?,
+                    mime: tokens.string()?,
+                }),
+                "dropped_file" => input.dropped_files.push(egui::DroppedFile {
+                    path: tokens.path()?,
+                    name: tokens.string()?,
+                    ..Default::default()
+                }),
+                "event" => input.events.push(tokens.event()?),
+                _ => return Err(invalid(line_no, "unknown field")),
+            }
+        }
+        if frame.is_some() {
+            return Err(invalid(
+                last_line,
+                "recording ends in the middle of a frame",
+            ));
+        }
+        Ok(Self { frames, next: 0 })
+    }
+
+    /// All the recorded frames.
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// The input of the next frame, None once every frame was replayed.
+    pub fn next_input(&mut self) -> Option<RawInput> {
+        let frame = self.frames.get(self.next)?;
+        self.next += 1;
+        Some(frame.input.clone())
+    }
+
+    /// Start over from the first frame.
+    pub fn rewind(&mut self) {
+        self.next = 0;
+    }
+
+    /// Run every remaining frame through `ctx`, returning each frame's output.
+    ///
+    /// Time only comes from the recorded input, so replays are deterministic.
+    pub fn replay(
+        &mut self,
+        ctx: &egui::Context,
+        mut run_ui: impl FnMut(&egui::Context),
+    ) -> Vec<egui::FullOutput> {
+        let mut outputs = Vec::new();
+        while let Some(input) = self.next_input() {
+            outputs.push(ctx.run(input, &mut run_ui));
+        }
+        outputs
+    }
+}
+
+fn invalid(line: usize, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("recording line {}: {}", line, msg),
+    )
+}
+
+fn write_frame(w: &mut impl Write, frame: &RecordedFrame) -> io::Result<()> {
+    let input = &frame.input;
+    writeln!(w, "frame {}", frame.elapsed)?;
+    if let Some(rect) = input.screen_rect {
+        writeln!(
+            w,
+            "screen_rect {} {} {} {}",
+            rect.min.x, rect.min.y, rect.max.x, rect.max.y
+        )?;
+    }
+    if let Some(ppp) = input.pixels_per_point {
+        writeln!(w, "pixels_per_point {}", ppp)?;
+    }
+    if let Some(side) = input.max_texture_side {
+        writeln!(w, "max_texture_side {}", side)?;
+    }
+    if let Some(time) = input.time {
+        writeln!(w, "time {}", time)?;
+    }
+    writeln!(w, "predicted_dt {}", input.predicted_dt)?;
+    writeln!(w, "modifiers {}", modifiers(&input.modifiers))?;
+    writeln!(w, "has_focus {}", input.has_focus as u8)?;
+    for file in &input.hovered_files {
+        writeln!(
+            w,
+            "hovered_file {} {}",
+            path(&file.path),
+            string(&file.mime)
+        )?;
+    }
+    for file in &input.dropped_files {
+        writeln!(
+            w,
+            "dropped_file {} {}",
+            path(&file.path),
+            string(&file.name)
+        )?;
+    }
+    for event in &input.events {
+        writeln!(w, "event {}", event_line(event))?;
+    }
+    writeln!(w, "end")
+}
+
+fn event_line(event: &Event) -> String {
+    match event {
+        Event::Copy => "copy".to_string(),
+        Event::Cut => "cut".to_string(),
+        Event::Paste(text) => format!("paste {}", string(text)),
+        Event::Text(text) => format!("text {}", string(text)),
+        Event::Key {
+            key,
+            pressed,
+            modifiers: mods,
+        } => format!("key {:?} {} {}", key, *pressed as u8, modifiers(mods)),
+        Event::PointerMoved(pos) => format!("pointer_moved {} {}", pos.x, pos.y),
+        Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers: mods,
+        } => format!(
+            "pointer_button {} {} {:?} {} {}",
+            pos.x,
+            pos.y,
+            button,
+            *pressed as u8,
+            modifiers(mods)
+        ),
+        Event::PointerGone => "pointer_gone".to_string(),
+        Event::Scroll(delta) => format!("scroll {} {}", delta.x, delta.y),
+        Event::Zoom(factor) => format!("zoom {}", factor),
+        Event::CompositionStart => "composition_start".to_string(),
+        Event::CompositionUpdate(text) => format!("composition_update {}", string(text)),
+        Event::CompositionEnd(text) => format!("composition_end {}", string(text)),
+        Event::Touch {
+            device_id,
+            id,
+            phase,
+            pos,
+            force,
+        } => format!(
+            "touch {} {} {:?} {} {} {}",
+            device_id.0, id.0, phase, pos.x, pos.y, force
+        ),
+    }
+}
+
+fn modifiers(mods: &Modifiers) -> String {
+    format!(
+        "{} {} {} {} {}",
+        mods.alt as u8, mods.ctrl as u8, mods.shift as u8, mods.mac_cmd as u8, mods.command as u8
+    )
+}
+
+/// Strings are prefixed with `'` (so they can be empty) and escaped to hold no spaces.
+fn string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 1);
+    out.push('\'');
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ' ' => out.push_str("\\s"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn path(path: &Option<PathBuf>) -> String {
+    match path {
+        Some(path) => string(&path.to_string_lossy()),
+        None => "-".to_string(),
+    }
+}
+
+struct Tokens<'a> {
+    iter: std::str::Split<'a, char>,
+    line: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> io::Result<&'a str> {
+        self.iter
+            .next()
+            .ok_or_else(|| invalid(self.line, "missing value"))
+    }
+
+    fn parse<T: std::str::FromStr>(&mut self) -> io::Result<T> {
+        let token = self.next()?;
+        token
+            .parse()
+            .map_err(|_| invalid(self.line, "invalid number"))
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        match self.next()? {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(invalid(self.line, "invalid boolean")),
+        }
+    }
+
+    fn modifiers(&mut self) -> io::Result<Modifiers> {
+        Ok(Modifiers {
+            alt: self.bool()?,
+            ctrl: self.bool()?,
+            shift: self.bool()?,
+            mac_cmd: self.bool()?,
+            command: self.bool()?,
+        })
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let token = self.next()?;
+        let mut chars = token
+            .strip_prefix('\'')
+            .ok_or_else(|| invalid(self.line, "invalid string"))?
+            .chars();
+        let mut out = String::with_capacity(token.len());
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('\\') => out.push('\\'),
+                Some('s') => out.push(' '),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                _ => return Err(invalid(self.line, "invalid escape")),
+            }
+        }
+        Ok(out)
+    }
+
+    fn path(&mut self) -> io::Result<Option<PathBuf>> {
+        if self.iter.clone().next() == Some("-") {
+            self.next()?;
+            return Ok(None);
+        }
+        Ok(Some(PathBuf::from(self.string()?)))
+    }
+
+    fn event(&mut self) -> io::Result<Event> {
+        let event = match self.next()? {
+            "copy" => Event::Copy,
+            "cut" => Event::Cut,
+            "paste" => Event::Paste(self.string()?),
+            "text" => Event::Text(self.string()?),
+            "key" => Event::Key {
+                key: self.key()?,
+                pressed: self.bool()?,
+                modifiers: self.modifiers()?,
+            },
+            "pointer_moved" => Event::PointerMoved(pos2(self.parse()?, self.parse()?)),
+            "pointer_button" => Event::PointerButton {
+                pos: pos2(self.parse()?, self.parse()?),
+                button: self.pointer_button()?,
+                pressed: self.bool()?,
+                modifiers: self.modifiers()?,
+            },
+            "pointer_gone" => Event::PointerGone,
+            "scroll" => Event::Scroll(egui::vec2(self.parse()?, self.parse()?)),
+            "zoom" => Event::Zoom(self.parse()?),
+            "composition_start" => Event::CompositionStart,
+            "composition_update" => Event::CompositionUpdate(self.string()?),
+            "composition_end" => Event::CompositionEnd(self.string()?),
+            "touch" => Event::Touch {
+                device_id: TouchDeviceId(self.parse()?),
+                id: TouchId(self.parse()?),
+                phase: self.touch_phase()?,
+                pos: pos2(self.parse()?, self.parse()?),
+                force: self.parse()?,
+            },
+            _ => return Err(invalid(self.line, "unknown event")),
+        };
+        Ok(event)
+    }
+
+    fn key(&mut self) -> io::Result<Key> {
+        let name = self.next()?;
+        ALL_KEYS
+            .iter()
+            .copied()
+            .find(|key| format!("{:?}", key) == name)
+            .ok_or_else(|| invalid(self.line, "unknown key"))
+    }
+
+    fn pointer_button(&mut self) -> io::Result<PointerButton> {
+        Ok(match self.next()? {
+            "Primary" => PointerButton::Primary,
+            "Secondary" => PointerButton::Secondary,
+            "Middle" => PointerButton::Middle,
+            "Extra1" => PointerButton::Extra1,
+            "Extra2" => PointerButton::Extra2,
+            _ => return Err(invalid(self.line, "unknown pointer button")),
+        })
+    }
+
+    fn touch_phase(&mut self) -> io::Result<TouchPhase> {
+        Ok(match self.next()? {
+            "Start" => TouchPhase::Start,
+            "Move" => TouchPhase::Move,
+            "End" => TouchPhase::End,
+            "Cancel" => TouchPhase::Cancel,
+            _ => return Err(invalid(self.line, "unknown touch phase")),
+        })
+    }
+}
+
+/// Every egui::Key, keys are stored by their Debug name.
+const ALL_KEYS: &[Key] = &[
+    Key::ArrowDown,
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::ArrowUp,
+    Key::Escape,
+    Key::Tab,
+    Key::Backspace,
+    Key::Enter,
+    Key::Space,
+    Key::Insert,
+    Key::Delete,
+    Key::Home,
+    Key::End,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::F13,
+    Key::F14,
+    Key::F15,
+    Key::F16,
+    Key::F17,
+    Key::F18,
+    Key::F19,
+    Key::F20,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> io::Result<InputReplayer> {
+        InputReplayer::read_from(text.as_bytes())
+    }
+
+    fn error(text: &str) -> String {
+        match read(text) {
+            Ok(_) => panic!("read {:?}", text),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn write_then_read_is_equal() {
+        let modifiers = Modifiers {
+            ctrl: true,
+            command: true,
+            ..Default::default()
+        };
+        let mut recorder = InputRecorder::new();
+        recorder.frames.push(RecordedFrame {
+            elapsed: 1.0 / 3.0,
+            input: RawInput {
+                screen_rect: Some(Rect::from_min_max(pos2(0.0, 0.0), pos2(533.3333, 400.1))),
+                pixels_per_point: Some(1.5),
+                max_texture_side: Some(2048),
+                time: Some(0.1 + 0.2),
+                predicted_dt: 1.0 / 60.0,
+                modifiers,
+                hovered_files: vec![HoveredFile {
+                    path: None,
+                    mime: "text/plain".into(),
+                }],
+                dropped_files: vec![egui::DroppedFile {
+                    path: Some("a dir/file.txt".into()),
+                    name: "file.txt".into(),
+                    ..Default::default()
+                }],
+                events: vec![
+                    Event::PointerMoved(pos2(0.1, -7.25e-5)),
+                    Event::PointerButton {
+                        pos: pos2(120.7, 48.0),
+                        button: PointerButton::Extra2,
+                        pressed: true,
+                        modifiers,
+                    },
+                    Event::Text("hello world\\\t''".into()),
+                    Event::Text(String::new()),
+                    Event::Key {
+                        key: Key::PageDown,
+                        pressed: false,
+                        modifiers,
+                    },
+                    Event::Scroll(egui::vec2(0.0, -12.5)),
+                    Event::Zoom(1.0 / 7.0),
+                    Event::Copy,
+                    Event::PointerGone,
+                    Event::Touch {
+                        device_id: TouchDeviceId(3),
+                        id: TouchId(u64::MAX),
+                        phase: TouchPhase::Cancel,
+                        pos: pos2(f32::MIN_POSITIVE, 1e10),
+                        force: 0.3,
+                    },
+                ],
+                ..Default::default()
+            },
+        });
+        recorder.frames.push(RecordedFrame {
+            elapsed: 2.5,
+            input: RawInput::default(),
+        });
+
+        let mut text = Vec::new();
+        recorder.write_to(&mut text).unwrap();
+        let replayer = InputReplayer::read_from(&text[..]).unwrap();
+        assert_eq!(replayer.frames(), recorder.frames());
+    }
+
+    #[test]
+    fn malformed_recordings_are_rejected() {
+        assert_eq!(error(""), "recording line 1: empty recording");
+        assert_eq!(
+            error("fltk-egui-recording 2\n"),
+            "recording line 1: unsupported recording version"
+        );
+        assert_eq!(
+            error("recording 1\n"),
+            "recording line 1: not an fltk-egui recording"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\ntime 1\n"),
+            "recording line 2: field outside of a frame"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\nframe 0\nframe 1\n"),
+            "recording line 3: frame without end"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\nframe 0\ntime x\nend\n"),
+            "recording line 3: invalid number"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\nframe 0\nevent key Nope 1 0 0 0 0 0\nend\n"),
+            "recording line 3: unknown key"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\nframe 0\nevent text hello\nend\n"),
+            "recording line 3: invalid string"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\nframe 0\nevent scroll 1\nend\n"),
+            "recording line 3: missing value"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\nframe 0\ncolor 1\nend\n"),
+            "recording line 3: unknown field"
+        );
+        assert_eq!(
+            error("fltk-egui-recording 1\nframe 0\ntime 1\n\n"),
+            "recording line 4: recording ends in the middle of a frame"
+        );
+    }
+}
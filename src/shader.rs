@@ -0,0 +1,76 @@
+use egui_glow::glow::{self, HasContext};
+use fltk::enums::Mode;
+
+/// The kind of shaders egui is painted with, see `BackendBuilder::shader_version`.
+///
+/// egui_glow picks its shaders from the GLSL version of the context, so the version is
+/// chosen through the context: the GlWindow's `Mode`. FLTK creates OpenGL ES contexts
+/// only where it uses EGL (e.g. embedded boards), which the mode doesn't select.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShaderVersion {
+    /// OpenGL 2.1 (GLSL 1.20), a compatibility context: old Mesa drivers, macOS without
+    /// `Mode::Opengl3`.
+    Gl120,
+    /// OpenGL 3.1 and later (GLSL 1.40), e.g. the core profile of `Mode::Opengl3`
+    /// (3.2 or later, depending on the platform).
+    Gl140,
+    /// OpenGL 3.3 core (GLSL 3.30) and later, with `Mode::Opengl3`.
+    Gl330,
+    /// OpenGL ES 2 (GLSL ES 1.00).
+    Es100,
+    /// OpenGL ES 3 (GLSL ES 3.00).
+    Es300,
+}
+
+impl ShaderVersion {
+    /// The version a GlWindow of `mode` is sure to get: Gl140 with `Mode::Opengl3`,
+    /// Gl120 otherwise.
+    pub fn from_mode(mode: Mode) -> Self {
+        if mode.contains(Mode::Opengl3) {
+            Self::Gl140
+        } else {
+            Self::Gl120
+        }
+    }
+
+    /// The version of the current context, from its `GL_SHADING_LANGUAGE_VERSION`.
+    pub fn detect(gl: &glow::Context) -> Self {
+        let version = unsafe { gl.get_parameter_string(glow::SHADING_LANGUAGE_VERSION) };
+        // e.g. "4.60 NVIDIA", "1.20", "OpenGL ES GLSL ES 3.00"
+        let number = version
+            .split_whitespace()
+            .find_map(|word| {
+                let mut parts = word.split('.');
+                let major: u32 = parts.next()?.parse().ok()?;
+                let minor: u32 = parts.next()?.get(..2)?.parse().ok()?;
+                Some(major * 100 + minor)
+            })
+            .unwrap_or(0);
+        match (version.contains(" ES "), number) {
+            (true, number) if number >= 300 => Self::Es300,
+            (true, _) => Self::Es100,
+            (false, number) if number >= 330 => Self::Gl330,
+            (false, number) if number >= 140 => Self::Gl140,
+            (false, _) => Self::Gl120,
+        }
+    }
+
+    pub fn is_es(self) -> bool {
+        matches!(self, Self::Es100 | Self::Es300)
+    }
+
+    /// Whether a context of this version can paint `wanted` shaders.
+    pub fn supports(self, wanted: Self) -> bool {
+        self.is_es() == wanted.is_es() && self >= wanted
+    }
+
+    /// `mode` with the profile flag of this version, unchanged for the ES versions.
+    pub(crate) fn apply_to_mode(self, mut mode: Mode) -> Mode {
+        match self {
+            Self::Gl120 => mode.remove(Mode::Opengl3),
+            Self::Gl140 | Self::Gl330 => mode.insert(Mode::Opengl3),
+            Self::Es100 | Self::Es300 => {}
+        }
+        mode
+    }
+}
@@ -0,0 +1,408 @@
+use crate::EguiState;
+use egui::{
+    epaint::{ImageDelta, Primitive, Vertex},
+    ClippedPrimitive, Color32, ColorImage, ImageData, Mesh, Pos2, TextureFilter, TextureId,
+    TexturesDelta,
+};
+use fltk::{
+    app, draw,
+    enums::ColorDepth,
+    prelude::{WidgetExt, WindowExt},
+};
+use std::collections::HashMap;
+
+const MAX_TEXTURE_SIDE: usize = 8192;
+
+/// Construct the backend for a widget drawn without OpenGL, e.g. a plain `Window` or a `Frame`.
+///
+/// Paint with `SoftwarePainter::paint_and_update_textures`, then blit the result from
+/// the widget's draw callback with `SoftwarePainter::draw`.
+pub fn with_software<W: WidgetExt>(widget: &mut W) -> (SoftwarePainter, EguiState) {
+    let screen = match widget.as_window().or_else(|| widget.window()) {
+        Some(win) => win.screen_num(),
+        None => 0,
+    };
+    app::set_screen_scale(screen, 1.);
+    app::keyboard_screen_scaling(false);
+    let painter = SoftwarePainter::new();
+    let state = EguiState::with_size(
+        [widget.w() as u32, widget.h() as u32],
+        1.0,
+        painter.max_texture_side(),
+    );
+    (painter, state)
+}
+
+struct Texture {
+    size: [usize; 2],
+    /// Premultiplied sRGBA.
+    pixels: Vec<Color32>,
+    filter: TextureFilter,
+}
+
+impl Texture {
+    fn sample(&self, uv: Pos2) -> Color32 {
+        let [w, h] = self.size;
+        if w == 0 || h == 0 {
+            return Color32::TRANSPARENT;
+        }
+        let texel = |x: isize, y: isize| {
+            let x = x.clamp(0, w as isize - 1) as usize;
+            let y = y.clamp(0, h as isize - 1) as usize;
+            self.pixels[y * w + x]
+        };
+        let x = uv.x * w as f32;
+        let y = uv.y * h as f32;
+        match self.filter {
+            TextureFilter::Nearest => texel(x.floor() as isize, y.floor() as isize),
+            TextureFilter::Linear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                let top = lerp_color(texel(x0, y0), texel(x0 + 1, y0), tx);
+                let bottom = lerp_color(texel(x0, y0 + 1), texel(x0 + 1, y0 + 1), tx);
+                lerp_color(top, bottom, ty)
+            }
+        }
+    }
+}
+
+/// Paints egui on the CPU into an RGBA buffer, for machines without usable OpenGL.
+///
+/// Mirrors `egui_glow::Painter`, except that `PaintCallback`s are skipped since they need GL.
+pub struct SoftwarePainter {
+    textures: HashMap<TextureId, Texture>,
+    size: [usize; 2],
+    /// Premultiplied RGBA, row by row.
+    pixels: Vec<u8>,
+    /// Unmultiplied copy for FLTK, only used when the buffer isn't opaque.
+    blit: Vec<u8>,
+    /// Color of the buffer before egui is painted, default value is the gray of egui's
+    /// dark panels. FLTK blends a translucent buffer over whatever the widget last drew,
+    /// so keep it opaque unless the widget paints its background first.
+    pub clear_color: Color32,
+}
+
+impl Default for SoftwarePainter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoftwarePainter {
+    pub fn new() -> Self {
+        Self {
+            textures: HashMap::new(),
+            size: [0, 0],
+            pixels: Vec::new(),
+            blit: Vec::new(),
+            clear_color: Color32::from_gray(27),
+        }
+    }
+
+    pub fn max_texture_side(&self) -> usize {
+        MAX_TEXTURE_SIDE
+    }
+
+    /// Width and height of the painted buffer in pixels.
+    pub fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    /// The painted pixels: premultiplied RGBA, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The last painted frame, as an egui image (premultiplied alpha).
+    pub fn capture(&self) -> ColorImage {
+        ColorImage {
+            size: self.size,
+            pixels: self
+                .pixels
+                .chunks_exact(4)
+                .map(|p| Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3]))
+                .collect(),
+        }
+    }
+
+    pub fn paint_and_update_textures(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clipped_primitives: &[ClippedPrimitive],
+        textures_delta: &TexturesDelta,
+    ) {
+        for (id, image_delta) in &textures_delta.set {
+            self.set_texture(*id, image_delta);
+        }
+
+        self.paint_primitives(screen_size_px, pixels_per_point, clipped_primitives);
+
+        for &id in &textures_delta.free {
+            self.free_texture(id);
+        }
+    }
+
+    /// Clear the buffer to `clear_color` and paint the primitives into it.
+    pub fn paint_primitives(
+        &mut self,
+        screen_size_px: [u32; 2],
+        pixels_per_point: f32,
+        clipped_primitives: &[ClippedPrimitive],
+    ) {
+        let [width, height] = [screen_size_px[0] as usize, screen_size_px[1] as usize];
+        self.size = [width, height];
+        let clear = self.clear_color.to_array();
+        self.pixels.clear();
+        self.pixels.reserve(width * height * 4);
+        for _ in 0..width * height {
+            self.pixels.extend_from_slice(&clear);
+        }
+
+        for ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in clipped_primitives
+        {
+            let clip = [
+                (clip_rect.min.x * pixels_per_point)
+                    .round()
+                    .clamp(0.0, width as f32) as usize,
+                (clip_rect.min.y * pixels_per_point)
+                    .round()
+                    .clamp(0.0, height as f32) as usize,
+                (clip_rect.max.x * pixels_per_point)
+                    .round()
+                    .clamp(0.0, width as f32) as usize,
+                (clip_rect.max.y * pixels_per_point)
+                    .round()
+                    .clamp(0.0, height as f32) as usize,
+            ];
+            if clip[0] >= clip[2] || clip[1] >= clip[3] {
+                continue;
+            }
+            match primitive {
+                Primitive::Mesh(mesh) => self.paint_mesh(clip, pixels_per_point, mesh),
+                // Callbacks paint with OpenGL, there is none here.
+                Primitive::Callback(_) => (),
+            }
+        }
+    }
+
+    fn paint_mesh(&mut self, clip: [usize; 4], pixels_per_point: f32, mesh: &Mesh) {
+        let texture = match self.textures.get(&mesh.texture_id) {
+            Some(texture) => texture,
+            None => return,
+        };
+        let mut target = Target {
+            pixels: &mut self.pixels,
+            width: self.size[0],
+            clip,
+        };
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertex = |i: u32| {
+                let mut vertex = mesh.vertices[i as usize];
+                vertex.pos = Pos2::new(
+                    vertex.pos.x * pixels_per_point,
+                    vertex.pos.y * pixels_per_point,
+                );
+                vertex
+            };
+            target.triangle(
+                texture,
+                [
+                    vertex(triangle[0]),
+                    vertex(triangle[1]),
+                    vertex(triangle[2]),
+                ],
+            );
+        }
+    }
+
+    pub fn set_texture(&mut self, tex_id: TextureId, delta: &ImageDelta) {
+        let (size, pixels): ([usize; 2], Vec<Color32>) = match &delta.image {
+            ImageData::Color(image) => (image.size, image.pixels.clone()),
+            // Same gamma as egui_glow.
+            ImageData::Font(image) => (image.size, image.srgba_pixels(1.0).collect()),
+        };
+
+        match delta.pos {
+            Some([x, y]) => {
+                let texture = match self.textures.get_mut(&tex_id) {
+                    Some(texture) => texture,
+                    None => return,
+                };
+                texture.filter = delta.filter;
+                let [w, h] = size;
+                for row in 0..h.min(texture.size[1].saturating_sub(y)) {
+                    let columns = w.min(texture.size[0].saturating_sub(x));
+                    let dst = (y + row) * texture.size[0] + x;
+                    texture.pixels[dst..dst + columns]
+                        .copy_from_slice(&pixels[row * w..row * w + columns]);
+                }
+            }
+            None => {
+                self.textures.insert(
+                    tex_id,
+                    Texture {
+                        size,
+                        pixels,
+                        filter: delta.filter,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn free_texture(&mut self, tex_id: TextureId) {
+        self.textures.remove(&tex_id);
+    }
+
+    /// Blit the buffer at x, y; call it from the widget's draw callback
+    /// (with the widget's x() and y(), or 0, 0 for a window).
+    pub fn draw(&mut self, x: i32, y: i32) {
+        let [width, height] = self.size;
+        if width == 0 || height == 0 {
+            return;
+        }
+        let opaque = self.pixels.chunks_exact(4).all(|pixel| pixel[3] == 255);
+        let pixels = if opaque {
+            &self.pixels
+        } else {
+            // FLTK blends unmultiplied RGBA.
+            self.blit.clear();
+            self.blit
+                .extend(self.pixels.chunks_exact(4).flat_map(|pixel| {
+                    Color32::from_rgba_premultiplied(pixel[0], pixel[1], pixel[2], pixel[3])
+                        .to_srgba_unmultiplied()
+                }));
+            &self.blit
+        };
+        draw::draw_image(pixels, x, y, width as i32, height as i32, ColorDepth::Rgba8).ok();
+    }
+}
+
+struct Target<'a> {
+    pixels: &'a mut [u8],
+    width: usize,
+    /// min x, min y, max x, max y (exclusive), in pixels.
+    clip: [usize; 4],
+}
+
+impl Target<'_> {
+    fn triangle(&mut self, texture: &Texture, mut v: [Vertex; 3]) {
+        let mut area = edge(v[0].pos, v[1].pos, v[2].pos);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        if area < 0.0 {
+            v.swap(1, 2);
+            area = -area;
+        }
+
+        let min_x = v.iter().map(|v| v.pos.x).fold(f32::INFINITY, f32::min);
+        let max_x = v.iter().map(|v| v.pos.x).fold(f32::NEG_INFINITY, f32::max);
+        let min_y = v.iter().map(|v| v.pos.y).fold(f32::INFINITY, f32::min);
+        let max_y = v.iter().map(|v| v.pos.y).fold(f32::NEG_INFINITY, f32::max);
+        let x0 = (min_x.floor().max(0.0) as usize).max(self.clip[0]);
+        let x1 = (max_x.ceil().max(0.0) as usize).min(self.clip[2]);
+        let y0 = (min_y.floor().max(0.0) as usize).max(self.clip[1]);
+        let y1 = (max_y.ceil().max(0.0) as usize).min(self.clip[3]);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        // Most shapes use a single texel (the white one of the font atlas) and a single color.
+        let same_uv = v[0].uv == v[1].uv && v[1].uv == v[2].uv;
+        let constant = if same_uv && v[0].color == v[1].color && v[1].color == v[2].color {
+            Some(mul_color(v[0].color, texture.sample(v[0].uv)))
+        } else {
+            None
+        };
+        let texel = if same_uv {
+            Some(texture.sample(v[0].uv))
+        } else {
+            None
+        };
+
+        // Edges shared by two triangles belong to only one of them, so they aren't blended twice.
+        let edges = [(1, 2), (2, 0), (0, 1)];
+        let owns_edge = edges.map(|(a, b)| {
+            let d = v[b].pos - v[a].pos;
+            d.y > 0.0 || (d.y == 0.0 && d.x < 0.0)
+        });
+
+        for y in y0..y1 {
+            let py = y as f32 + 0.5;
+            for x in x0..x1 {
+                let p = Pos2::new(x as f32 + 0.5, py);
+                let mut w = [0.0; 3];
+                let mut inside = true;
+                for (i, &(a, b)) in edges.iter().enumerate() {
+                    w[i] = edge(v[a].pos, v[b].pos, p);
+                    if w[i] < 0.0 || (w[i] == 0.0 && !owns_edge[i]) {
+                        inside = false;
+                        break;
+                    }
+                }
+                if !inside {
+                    continue;
+                }
+
+                let src = match constant {
+                    Some(color) => color,
+                    None => {
+                        let w = w.map(|w| w / area);
+                        let color = interpolate_color(&v, w);
+                        let texel = texel.unwrap_or_else(|| {
+                            let uv = Pos2::new(
+                                v[0].uv.x * w[0] + v[1].uv.x * w[1] + v[2].uv.x * w[2],
+                                v[0].uv.y * w[0] + v[1].uv.y * w[1] + v[2].uv.y * w[2],
+                            );
+                            texture.sample(uv)
+                        });
+                        mul_color(color, texel)
+                    }
+                };
+                if src == Color32::TRANSPARENT {
+                    continue;
+                }
+                let i = (y * self.width + x) * 4;
+                blend(&mut self.pixels[i..i + 4], src);
+            }
+        }
+    }
+}
+
+fn edge(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+fn interpolate_color(v: &[Vertex; 3], w: [f32; 3]) -> Color32 {
+    let channel = |c: usize| {
+        let value =
+            v[0].color[c] as f32 * w[0] + v[1].color[c] as f32 * w[1] + v[2].color[c] as f32 * w[2];
+        value.round().clamp(0.0, 255.0) as u8
+    };
+    Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    let channel = |c: usize| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * t).round() as u8;
+    Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+fn mul_color(a: Color32, b: Color32) -> Color32 {
+    let channel = |c: usize| ((a[c] as u32 * b[c] as u32 + 127) / 255) as u8;
+    Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+/// Premultiplied "over" blending.
+fn blend(dst: &mut [u8], src: Color32) {
+    let inv = 255 - src.a() as u32;
+    for (c, dst) in dst.iter_mut().enumerate() {
+        *dst = (src[c] as u32 + (*dst as u32 * inv + 127) / 255).min(255) as u8;
+    }
+}
@@ -0,0 +1,225 @@
+use egui::{epaint::Primitive, ClippedPrimitive, TexturesDelta};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Frames kept for the averages and the graph of the overlay.
+const HISTORY: usize = 120;
+
+/// A step of a frame, timed with `EguiState::end_phase`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FramePhase {
+    /// `egui::Context::run`
+    Run,
+    /// `egui::Context::tessellate`
+    Tessellate,
+    /// `Painter::paint_and_update_textures`
+    Paint,
+    /// `GlWindow::swap_buffers`
+    Swap,
+}
+
+/// What a frame cost, see `EguiState::frame_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// Spent translating FLTK events to egui since the previous frame.
+    pub input: Duration,
+    pub run: Duration,
+    pub tessellate: Duration,
+    pub paint: Duration,
+    pub swap: Duration,
+    /// From take_input() to the last phase.
+    pub total: Duration,
+    /// Events given to egui.
+    pub events: usize,
+    /// Meshes and paint callbacks.
+    pub primitives: usize,
+    pub vertices: usize,
+    pub indices: usize,
+    /// Textures created or updated, and their size in bytes.
+    pub textures_set: usize,
+    pub texture_bytes: usize,
+    pub textures_freed: usize,
+}
+
+impl FrameStats {
+    /// Count the tessellated meshes of the frame.
+    pub fn count_primitives(&mut self, primitives: &[ClippedPrimitive]) {
+        self.primitives += primitives.len();
+        for clipped in primitives {
+            if let Primitive::Mesh(mesh) = &clipped.primitive {
+                self.vertices += mesh.vertices.len();
+                self.indices += mesh.indices.len();
+            }
+        }
+    }
+
+    /// Count the texture uploads and frees of the frame.
+    pub fn count_textures(&mut self, textures_delta: &TexturesDelta) {
+        self.textures_set += textures_delta.set.len();
+        self.textures_freed += textures_delta.free.len();
+        for (_, delta) in &textures_delta.set {
+            let image = &delta.image;
+            self.texture_bytes += image.width() * image.height() * image.bytes_per_pixel();
+        }
+    }
+}
+
+/// Collects the stats of the current frame and keeps those of the recent ones.
+#[derive(Default)]
+pub(crate) struct FrameStatsCollector {
+    pending_input: Duration,
+    frame_start: Option<Instant>,
+    phase_start: Option<Instant>,
+    current: FrameStats,
+    history: VecDeque<FrameStats>,
+}
+
+impl FrameStatsCollector {
+    pub fn add_input(&mut self, duration: Duration) {
+        self.pending_input += duration;
+    }
+
+    /// take_input() started a new frame, the previous one is complete.
+    pub fn begin_frame(&mut self, events: usize) {
+        if self.frame_start.is_some() {
+            if self.history.len() == HISTORY {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.current);
+        }
+        let now = Instant::now();
+        self.frame_start = Some(now);
+        self.phase_start = Some(now);
+        self.current = FrameStats {
+            input: std::mem::take(&mut self.pending_input),
+            events,
+            ..Default::default()
+        };
+    }
+
+    pub fn end_phase(&mut self, phase: FramePhase) {
+        let (frame_start, phase_start) = match (self.frame_start, self.phase_start) {
+            (Some(frame_start), Some(phase_start)) => (frame_start, phase_start),
+            _ => return,
+        };
+        let now = Instant::now();
+        let duration = now - phase_start;
+        match phase {
+            FramePhase::Run => self.current.run += duration,
+            FramePhase::Tessellate => self.current.tessellate += duration,
+            FramePhase::Paint => self.current.paint += duration,
+            FramePhase::Swap => self.current.swap += duration,
+        }
+        self.current.total = now - frame_start;
+        self.phase_start = Some(now);
+    }
+
+    pub fn current_mut(&mut self) -> &mut FrameStats {
+        &mut self.current
+    }
+
+    pub fn history(&self) -> &VecDeque<FrameStats> {
+        &self.history
+    }
+
+    /// Draws the last frame's stats, the recent averages and a graph of the frame times.
+    pub fn show(&self, ctx: &egui::Context) {
+        let last = match self.history.back() {
+            Some(last) => *last,
+            None => return,
+        };
+        let frames = self.history.len() as u32;
+        let mean = |f: fn(&FrameStats) -> Duration| -> Duration {
+            self.history.iter().map(f).sum::<Duration>() / frames
+        };
+        let max_total = self
+            .history
+            .iter()
+            .map(|s| s.total)
+            .max()
+            .unwrap_or_default();
+
+        egui::Window::new("Frame stats")
+            .resizable(false)
+            .default_pos([8.0, 8.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("fltk_egui_frame_stats")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("");
+                        ui.strong("last");
+                        ui.strong(format!("mean of {}", frames));
+                        ui.end_row();
+                        let rows: [(&str, fn(&FrameStats) -> Duration); 6] = [
+                            ("input", |s| s.input),
+                            ("run", |s| s.run),
+                            ("tessellate", |s| s.tessellate),
+                            ("paint", |s| s.paint),
+                            ("swap", |s| s.swap),
+                            ("total", |s| s.total),
+                        ];
+                        for (name, f) in rows {
+                            ui.label(name);
+                            ui.monospace(format_ms(f(&last)));
+                            ui.monospace(format_ms(mean(f)));
+                            ui.end_row();
+                        }
+                    });
+                ui.separator();
+                ui.monospace(format!(
+                    "{} events, {} primitives, {} vertices, {} indices",
+                    last.events, last.primitives, last.vertices, last.indices
+                ));
+                ui.monospace(format!(
+                    "textures: {} set ({} KiB), {} freed",
+                    last.textures_set,
+                    last.texture_bytes / 1024,
+                    last.textures_freed
+                ));
+                ui.monospace(format!("slowest frame: {}", format_ms(max_total)));
+                self.graph(ui, max_total);
+            });
+    }
+
+    /// One bar per frame, the line marks 16.7 ms (60 fps).
+    fn graph(&self, ui: &mut egui::Ui, max_total: Duration) {
+        let budget = 1.0 / 60.0;
+        let scale = max_total.as_secs_f32().max(budget * 1.25);
+        let size = egui::vec2(ui.available_width().max(HISTORY as f32 * 2.0), 48.0);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let bar_width = rect.width() / HISTORY as f32;
+        let offset = HISTORY - self.history.len();
+        for (i, stats) in self.history.iter().enumerate() {
+            let secs = stats.total.as_secs_f32();
+            let x = rect.left() + (offset + i) as f32 * bar_width;
+            let top = rect.bottom() - rect.height() * (secs / scale).min(1.0);
+            let color = if secs > budget {
+                egui::Color32::from_rgb(230, 90, 60)
+            } else {
+                egui::Color32::from_rgb(90, 190, 90)
+            };
+            painter.rect_filled(
+                egui::Rect::from_x_y_ranges(x..=x + bar_width, top..=rect.bottom()),
+                0.0,
+                color,
+            );
+        }
+
+        let y = rect.bottom() - rect.height() * budget / scale;
+        painter.hline(
+            rect.x_range(),
+            y,
+            egui::Stroke::new(1.0, ui.visuals().text_color()),
+        );
+    }
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:6.2} ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,515 @@
+use crate::postscript::PostScript;
+use egui::{
+    emath::Rot2,
+    epaint::{
+        tessellator::path::rounded_rectangle, CircleShape, ClippedShape, Mesh, PathShape,
+        RectShape, TextShape,
+    },
+    Color32, ColorImage, FontFamily, ImageData, Pos2, Rect, Shape, Stroke, TextureId,
+    TexturesDelta, Vec2,
+};
+use fltk::{
+    draw,
+    enums::{Color, ColorDepth, Font, LineStyle},
+    image::RgbImage,
+    prelude::{FltkError, ImageExt, SurfaceDevice},
+    surface::SvgFileSurface,
+};
+use std::{collections::HashMap, path::Path};
+
+/// Draws egui shapes with `fltk::draw`, on whatever surface is current: an
+/// `SvgFileSurface` (see `export_svg`), a `Printer` or an `ImageSurface`, or into a
+/// PostScript file (see `export_ps`).
+///
+/// Unlike painting the tessellated meshes, lines, shapes and text stay vectors: text is
+/// drawn with FLTK's Helvetica and Courier fonts at the positions egui laid it out.
+/// FLTK has no alpha, translucent colors are blended over `background`. Images are
+/// drawn from the textures given to `update_textures` or `set_image`, other meshes as
+/// flat triangles.
+///
+/// ```no_run
+/// # let ctx = fltk_egui::egui::Context::default();
+/// # let input = fltk_egui::egui::RawInput::default();
+/// let output = ctx.run(input, |ctx| { /* ... */ });
+/// let screen = ctx.input().screen_rect();
+/// let painter = fltk_egui::VectorPainter::new();
+/// painter.export_svg("report.svg", screen, &output.shapes).unwrap();
+/// painter.export_ps("report.ps", screen, &output.shapes).unwrap();
+/// ```
+pub struct VectorPainter {
+    /// FLTK units per egui point, default value is 1.0.
+    pub scale: f32,
+    /// Where the top left of the egui rect is drawn, in FLTK units.
+    pub origin: (f32, f32),
+    /// What translucent colors are blended over, default value is egui's dark panel color.
+    pub background: Color32,
+    images: HashMap<TextureId, ColorImage>,
+}
+
+impl Default for VectorPainter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VectorPainter {
+    pub fn new() -> Self {
+        Self {
+            scale: 1.0,
+            origin: (0.0, 0.0),
+            background: egui::Visuals::dark().window_fill(),
+            images: HashMap::new(),
+        }
+    }
+
+    /// Keep the images uploaded in `textures_delta`, to draw them with the shapes.
+    ///
+    /// Feed it the deltas of every frame, the textures are only sent once.
+    pub fn update_textures(&mut self, textures_delta: &TexturesDelta) {
+        for (id, delta) in &textures_delta.set {
+            // The font atlas isn't needed, text is drawn with FLTK fonts.
+            if let (ImageData::Color(image), None) = (&delta.image, delta.pos) {
+                self.images.insert(*id, image.clone());
+            }
+        }
+        for id in &textures_delta.free {
+            self.images.remove(id);
+        }
+    }
+
+    /// Image to draw for meshes textured with `id`, e.g. a `TextureId::User`.
+    pub fn set_image(&mut self, id: TextureId, image: ColorImage) {
+        self.images.insert(id, image);
+    }
+
+    /// Draw `shapes`, whose `rect` (in points) starts at `origin`, on the current surface.
+    pub fn paint(&self, rect: Rect, shapes: &[ClippedShape]) {
+        self.paint_at(rect, shapes, self.scale, self.origin);
+    }
+
+    /// paint() with another scale and origin.
+    pub(crate) fn paint_at(
+        &self,
+        rect: Rect,
+        shapes: &[ClippedShape],
+        scale: f32,
+        origin: (f32, f32),
+    ) {
+        self.paint_on(&mut FltkDraw, rect, shapes, scale, origin);
+        draw::set_line_style(LineStyle::Solid, 0);
+    }
+
+    fn paint_on(
+        &self,
+        canvas: &mut impl Canvas,
+        rect: Rect,
+        shapes: &[ClippedShape],
+        scale: f32,
+        origin: (f32, f32),
+    ) {
+        let mut painter = Transform {
+            painter: self,
+            canvas,
+            scale,
+            offset: Vec2::new(origin.0, origin.1) - rect.min.to_vec2() * scale,
+        };
+        for ClippedShape(clip_rect, shape) in shapes {
+            let clip_rect = clip_rect.intersect(rect);
+            if !clip_rect.is_positive() {
+                continue;
+            }
+            let clip_rect =
+                Rect::from_min_max(painter.point(clip_rect.min), painter.point(clip_rect.max));
+            painter.canvas.push_clip(clip_rect);
+            painter.shape(shape);
+            painter.canvas.pop_clip();
+        }
+    }
+
+    /// Export `shapes` (the `FullOutput::shapes` of a frame, before tessellation) covering
+    /// `rect` (usually the screen rect) as an SVG file, of the size of `rect` times `scale`.
+    ///
+    /// Drawn with `fltk::draw`, whose line widths are whole units: stroke widths are
+    /// rounded, thin egui lines (e.g. 0.5 points) come out 1 unit wide. A larger `scale`
+    /// keeps them closer to their proportions. Meshes other than images are flattened to
+    /// triangles of one color each, the average of their vertex colors, so gradients and
+    /// feathered edges show as steps.
+    pub fn export_svg(
+        &self,
+        path: impl AsRef<Path>,
+        rect: Rect,
+        shapes: &[ClippedShape],
+    ) -> Result<(), FltkError> {
+        // The surface doesn't report errors, check the file can be written.
+        std::fs::File::create(path.as_ref()).map_err(FltkError::IoError)?;
+        let surface = SvgFileSurface::new(
+            (rect.width() * self.scale).ceil() as i32,
+            (rect.height() * self.scale).ceil() as i32,
+            path,
+        );
+        SvgFileSurface::push_current(&surface);
+        self.paint(rect, shapes);
+        SvgFileSurface::pop_current();
+        // The file is written when the surface is deleted.
+        drop(surface);
+        Ok(())
+    }
+
+    /// Export `shapes` covering `rect` as a PostScript file, see `export_svg`.
+    ///
+    /// The file is written directly, with the standard PostScript fonts (Helvetica and
+    /// Courier) for text, since fltk-rs has no PostScript file surface. Stroke widths are
+    /// kept as they are, meshes are flattened the same way.
+    pub fn export_ps(
+        &self,
+        path: impl AsRef<Path>,
+        rect: Rect,
+        shapes: &[ClippedShape],
+    ) -> Result<(), FltkError> {
+        let mut ps = PostScript::new(rect.width() * self.scale, rect.height() * self.scale);
+        self.paint_on(&mut ps, rect, shapes, self.scale, self.origin);
+        std::fs::write(path, ps.finish()).map_err(FltkError::IoError)
+    }
+
+    /// Opaque color of `color` over the background.
+    fn color(&self, color: Color32) -> Color {
+        let alpha = 1.0 - color.a() as f32 / 255.0;
+        let [r, g, b, _] = color.to_array();
+        let [br, bg, bb, _] = self.background.to_array();
+        let over = |c: u8, b: u8| (c as f32 + b as f32 * alpha).round().min(255.0) as u8;
+        Color::from_rgb(over(r, br), over(g, bg), over(b, bb))
+    }
+}
+
+/// Where `Transform` draws: fltk::draw (`FltkDraw`) or a PostScript file.
+///
+/// Coordinates are in FLTK units, y down. Colors are opaque, see `VectorPainter::color`.
+pub(crate) trait Canvas {
+    fn set_color(&mut self, color: Color);
+    fn set_line_width(&mut self, width: f32);
+    fn stroke(&mut self, points: &[Pos2], closed: bool);
+    fn fill(&mut self, points: &[Pos2]);
+    fn stroke_circle(&mut self, center: Pos2, radius: f32);
+    fn fill_circle(&mut self, center: Pos2, radius: f32);
+    fn set_font(&mut self, font: Font, size: f32);
+    /// Descent of the current font, below the baseline.
+    fn descent(&mut self) -> f32;
+    /// Draw `text` from `pos` on its baseline, turned `degrees` counter-clockwise.
+    fn text(&mut self, text: &str, pos: Pos2, degrees: i32);
+    /// Draw an RGB image scaled to `rect`.
+    fn image(&mut self, rgb: &[u8], size: [usize; 2], rect: Rect);
+    fn push_clip(&mut self, rect: Rect);
+    fn pop_clip(&mut self);
+}
+
+/// Draws on the current FLTK surface.
+struct FltkDraw;
+
+impl FltkDraw {
+    fn vertices(points: &[Pos2]) {
+        for pos in points {
+            draw::vertex(pos.x as f64, pos.y as f64);
+        }
+    }
+}
+
+impl Canvas for FltkDraw {
+    fn set_color(&mut self, color: Color) {
+        draw::set_draw_color(color);
+    }
+
+    fn set_line_width(&mut self, width: f32) {
+        // FLTK only has integer widths, see export_svg.
+        draw::set_line_style(LineStyle::Solid, width.round().max(1.0) as i32);
+    }
+
+    fn stroke(&mut self, points: &[Pos2], closed: bool) {
+        if closed {
+            draw::begin_loop();
+            Self::vertices(points);
+            draw::end_loop();
+        } else {
+            draw::begin_line();
+            Self::vertices(points);
+            draw::end_line();
+        }
+    }
+
+    fn fill(&mut self, points: &[Pos2]) {
+        draw::begin_complex_polygon();
+        Self::vertices(points);
+        draw::end_complex_polygon();
+    }
+
+    fn stroke_circle(&mut self, center: Pos2, radius: f32) {
+        draw::begin_loop();
+        draw::draw_circle(center.x as f64, center.y as f64, radius as f64);
+        draw::end_loop();
+    }
+
+    fn fill_circle(&mut self, center: Pos2, radius: f32) {
+        draw::begin_polygon();
+        draw::draw_circle(center.x as f64, center.y as f64, radius as f64);
+        draw::end_polygon();
+    }
+
+    fn set_font(&mut self, font: Font, size: f32) {
+        draw::set_font(font, size.round().max(1.0) as i32);
+    }
+
+    fn descent(&mut self) -> f32 {
+        draw::descent() as f32
+    }
+
+    fn text(&mut self, text: &str, pos: Pos2, degrees: i32) {
+        let (x, y) = (pos.x.round() as i32, pos.y.round() as i32);
+        if degrees == 0 {
+            draw::draw_text(text, x, y);
+        } else {
+            draw::draw_text_angled(degrees, text, x, y);
+        }
+    }
+
+    fn image(&mut self, rgb: &[u8], size: [usize; 2], rect: Rect) {
+        let (x, y) = (rect.min.x.round() as i32, rect.min.y.round() as i32);
+        let (w, h) = (rect.max.x.round() as i32 - x, rect.max.y.round() as i32 - y);
+        if let Ok(mut rgb) = RgbImage::new(rgb, size[0] as i32, size[1] as i32, ColorDepth::Rgb8) {
+            rgb.scale(w, h, false, true);
+            rgb.draw(x, y, w, h);
+        }
+    }
+
+    fn push_clip(&mut self, rect: Rect) {
+        let (x, y) = (rect.min.x.floor() as i32, rect.min.y.floor() as i32);
+        draw::push_clip(
+            x,
+            y,
+            rect.max.x.ceil() as i32 - x,
+            rect.max.y.ceil() as i32 - y,
+        );
+    }
+
+    fn pop_clip(&mut self) {
+        draw::pop_clip();
+    }
+}
+
+struct Transform<'a, C> {
+    painter: &'a VectorPainter,
+    canvas: &'a mut C,
+    scale: f32,
+    offset: Vec2,
+}
+
+impl<C: Canvas> Transform<'_, C> {
+    fn point(&self, pos: Pos2) -> Pos2 {
+        (pos.to_vec2() * self.scale + self.offset).to_pos2()
+    }
+
+    fn points(&self, points: &[Pos2]) -> Vec<Pos2> {
+        points.iter().map(|&pos| self.point(pos)).collect()
+    }
+
+    fn shape(&mut self, shape: &Shape) {
+        match shape {
+            Shape::Noop | Shape::Callback(_) => {}
+            Shape::Vec(shapes) => shapes.iter().for_each(|shape| self.shape(shape)),
+            Shape::Circle(circle) => self.circle(circle),
+            Shape::LineSegment { points, stroke } => self.polyline(points, false, *stroke),
+            Shape::Path(path) => self.path(path),
+            Shape::Rect(rect) => self.rect(rect),
+            Shape::Text(text) => self.text(text),
+            Shape::Mesh(mesh) => self.mesh(mesh),
+            Shape::QuadraticBezier(bezier) => self.path(&PathShape {
+                points: bezier.flatten(None),
+                closed: bezier.closed,
+                fill: bezier.fill,
+                stroke: bezier.stroke,
+            }),
+            Shape::CubicBezier(bezier) => self.path(&PathShape {
+                points: bezier.flatten(None),
+                closed: bezier.closed,
+                fill: bezier.fill,
+                stroke: bezier.stroke,
+            }),
+        }
+    }
+
+    fn set_stroke(&mut self, stroke: Stroke) -> bool {
+        if stroke.is_empty() {
+            return false;
+        }
+        self.canvas.set_color(self.painter.color(stroke.color));
+        self.canvas.set_line_width(stroke.width * self.scale);
+        true
+    }
+
+    fn set_fill(&mut self, fill: Color32) -> bool {
+        if fill == Color32::TRANSPARENT {
+            return false;
+        }
+        self.canvas.set_color(self.painter.color(fill));
+        true
+    }
+
+    fn polyline(&mut self, points: &[Pos2], closed: bool, stroke: Stroke) {
+        if points.len() < 2 || !self.set_stroke(stroke) {
+            return;
+        }
+        let points = self.points(points);
+        self.canvas.stroke(&points, closed);
+    }
+
+    fn polygon(&mut self, points: &[Pos2], fill: Color32) {
+        if points.len() < 3 || !self.set_fill(fill) {
+            return;
+        }
+        let points = self.points(points);
+        self.canvas.fill(&points);
+    }
+
+    fn path(&mut self, path: &PathShape) {
+        if path.closed {
+            self.polygon(&path.points, path.fill);
+        }
+        self.polyline(&path.points, path.closed, path.stroke);
+    }
+
+    fn circle(&mut self, circle: &CircleShape) {
+        let center = self.point(circle.center);
+        let radius = circle.radius * self.scale;
+        if self.set_fill(circle.fill) {
+            self.canvas.fill_circle(center, radius);
+        }
+        if self.set_stroke(circle.stroke) {
+            self.canvas.stroke_circle(center, radius);
+        }
+    }
+
+    fn rect(&mut self, rect: &RectShape) {
+        let mut points = vec![];
+        rounded_rectangle(&mut points, rect.rect, rect.rounding);
+        self.polygon(&points, rect.fill);
+        self.polyline(&points, true, rect.stroke);
+    }
+
+    fn text(&mut self, text: &TextShape) {
+        let scale = self.scale;
+        let rotation = Rot2::from_angle(text.angle);
+        // FLTK angles are counter-clockwise, in degrees.
+        let degrees = -text.angle.to_degrees().round() as i32;
+        let galley = &text.galley;
+
+        for row in &galley.rows {
+            let mut glyphs = row.glyphs.iter().peekable();
+            while let Some(first) = glyphs.next() {
+                let mut run = first.chr.to_string();
+                let mut last = first;
+                while let Some(glyph) =
+                    glyphs.next_if(|glyph| glyph.section_index == first.section_index)
+                {
+                    run.push(glyph.chr);
+                    last = glyph;
+                }
+                let format = &galley.job.sections[first.section_index as usize].format;
+                let run_rect = Rect::from_min_max(
+                    first.pos,
+                    Pos2::new(last.max_x(), first.pos.y + first.size.y),
+                );
+                let at = |pos: Pos2| text.pos + rotation * pos.to_vec2();
+
+                if format.background != Color32::TRANSPARENT && text.angle == 0.0 {
+                    self.polygon(
+                        &[
+                            at(run_rect.left_top()),
+                            at(run_rect.right_top()),
+                            at(run_rect.right_bottom()),
+                            at(run_rect.left_bottom()),
+                        ],
+                        format.background,
+                    );
+                }
+                if !run.trim().is_empty() {
+                    let font = match (&format.font_id.family, format.italics) {
+                        (FontFamily::Monospace, false) => Font::Courier,
+                        (FontFamily::Monospace, true) => Font::CourierItalic,
+                        (_, false) => Font::Helvetica,
+                        (_, true) => Font::HelveticaItalic,
+                    };
+                    self.canvas.set_font(font, format.font_id.size * scale);
+                    let color = text.override_text_color.unwrap_or(format.color);
+                    self.canvas.set_color(self.painter.color(color));
+                    // The glyph rect is one font size high, text is drawn on the baseline.
+                    let baseline = run_rect.top() + first.size.y - self.canvas.descent() / scale;
+                    let pos = self.point(at(Pos2::new(run_rect.left(), baseline)));
+                    self.canvas.text(&run, pos, degrees);
+                }
+                let strikethrough_y = run_rect.center().y;
+                for (stroke, y) in [
+                    (format.underline, run_rect.bottom()),
+                    (format.strikethrough, strikethrough_y),
+                    (text.underline, run_rect.bottom()),
+                ] {
+                    let line = [
+                        at(Pos2::new(run_rect.left(), y)),
+                        at(Pos2::new(run_rect.right(), y)),
+                    ];
+                    self.polyline(&line, false, stroke);
+                }
+            }
+        }
+    }
+
+    fn mesh(&mut self, mesh: &Mesh) {
+        let painter = self.painter;
+        if let Some(image) = painter.images.get(&mesh.texture_id) {
+            self.image(mesh, image);
+            return;
+        }
+        // Untextured (font texture white pixel) or unknown texture: flat triangles.
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertices = triangle.iter().map(|&i| &mesh.vertices[i as usize]);
+            let color = vertices
+                .clone()
+                .map(|v| v.color.to_array().map(|c| c as u32))
+                .fold([0; 4], |sum, c| {
+                    [sum[0] + c[0], sum[1] + c[1], sum[2] + c[2], sum[3] + c[3]]
+                })
+                .map(|c| (c / 3) as u8);
+            let color = Color32::from_rgba_premultiplied(color[0], color[1], color[2], color[3]);
+            let points: Vec<Pos2> = vertices.map(|v| v.pos).collect();
+            self.polygon(&points, color);
+        }
+    }
+
+    /// Draws the part of `image` mapped by the mesh's uvs over the mesh's bounds,
+    /// which is what egui's image meshes (textured rectangles) are.
+    fn image(&mut self, mesh: &Mesh, image: &ColorImage) {
+        let bounds = mesh.calc_bounds();
+        let uv = mesh.vertices.iter().fold(Rect::NOTHING, |uv, vertex| {
+            uv.union(Rect::from_two_pos(vertex.uv, vertex.uv))
+        });
+        let [width, height] = image.size;
+        let x0 = ((uv.min.x * width as f32).floor().max(0.0) as usize).min(width);
+        let y0 = ((uv.min.y * height as f32).floor().max(0.0) as usize).min(height);
+        let x1 = ((uv.max.x * width as f32).ceil() as usize).clamp(x0, width);
+        let y1 = ((uv.max.y * height as f32).ceil() as usize).clamp(y0, height);
+        if x1 == x0 || y1 == y0 || !bounds.is_positive() {
+            return;
+        }
+
+        // Tinted like egui does, and over the background since the canvas draws RGB.
+        let tint = mesh.vertices[0].color;
+        let mut rgb = Vec::with_capacity((x1 - x0) * (y1 - y0) * 3);
+        for y in y0..y1 {
+            for pixel in &image.pixels[y * width + x0..y * width + x1] {
+                let pixel = egui::Rgba::from(*pixel) * egui::Rgba::from(tint);
+                let color = self.painter.color(Color32::from(pixel));
+                let (r, g, b) = color.to_rgb();
+                rgb.extend_from_slice(&[r, g, b]);
+            }
+        }
+        let rect = Rect::from_min_max(self.point(bounds.min), self.point(bounds.max));
+        self.canvas.image(&rgb, [x1 - x0, y1 - y0], rect);
+    }
+}
@@ -0,0 +1,60 @@
+/// What Ctrl+mouse wheel does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoomGesture {
+    /// Nothing, the wheel is ignored while Ctrl is held.
+    None,
+    /// Send `egui::Event::Zoom` for widgets to handle (e.g. plots), scaled by `EguiState::zoom_factor`.
+    Event,
+    /// Zoom the whole ui, see `EguiState::zoom_in`/`zoom_out`.
+    Ui,
+}
+
+/// Settings of the ui zoom, see `EguiState::set_zoom`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZoomSettings {
+    /// What Ctrl+wheel does, default value is ZoomGesture::Event.
+    pub wheel: ZoomGesture,
+    /// Zoom the ui with Ctrl+Plus, Ctrl+Minus and reset it with Ctrl+0, default value is true.
+    pub keyboard_shortcuts: bool,
+    /// Zoom multiplier of a single wheel notch or shortcut, default value is 1.1
+    pub step: f32,
+    /// default value is 0.25
+    pub min: f32,
+    /// default value is 5.0
+    pub max: f32,
+}
+
+impl Default for ZoomSettings {
+    fn default() -> Self {
+        Self {
+            wheel: ZoomGesture::Event,
+            keyboard_shortcuts: true,
+            step: 1.1,
+            min: 0.25,
+            max: 5.0,
+        }
+    }
+}
+
+pub(crate) enum ZoomShortcut {
+    In,
+    Out,
+    Reset,
+}
+
+/// The zoom shortcut a key press (with Ctrl/Cmd held) stands for.
+pub(crate) fn zoom_shortcut(key: fltk::enums::Key) -> Option<ZoomShortcut> {
+    let bits = key.bits();
+    // FLTK keypad keys are FL_KP + the ASCII char.
+    let bits = if (0xff80..=0xffbd).contains(&bits) {
+        bits - 0xff80
+    } else {
+        bits
+    };
+    match char::from_u32(bits as u32)? {
+        '+' | '=' => Some(ZoomShortcut::In),
+        '-' => Some(ZoomShortcut::Out),
+        '0' => Some(ZoomShortcut::Reset),
+        _ => None,
+    }
+}